@@ -1,16 +1,37 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{BufWriter, ErrorKind},
+    path::PathBuf,
+    time::Instant,
+};
+
+use crossterm::event::KeyEvent;
 
 use anyhow::Result;
+use chrono::TimeZone;
 
 use tui::{
-    style::{Color, Style},
+    style::Style,
     widgets::{Block, Borders},
 };
 
-use crossterm::event::KeyModifiers;
+use crate::config::{Config, Preset, Theme};
+use crate::keymap::{self, Action, Context, Keymap};
+use crate::{client::*, clipboard, components::*};
+use crate::{
+    models::{
+        split_range, Duration, ExportFormat, History, LiveTail, Notification, Notifications,
+        QueryProgress, SearchMode,
+    },
+    option::Opt,
+};
 
-use crate::{client::*, components::*};
-use crate::{models::Duration, option::Opt};
+/// How far back streams are listed while live tailing.
+const LIVE_STREAMS_SECS: i64 = 60 * 60;
+
+/// How many events before and after an event its context shows.
+const CONTEXT_EVENTS: i32 = 50;
 
 #[derive(Debug, PartialEq)]
 pub enum FocusTarget {
@@ -18,6 +39,7 @@ pub enum FocusTarget {
     Duration,
     GroupFilter,
     Groups,
+    Streams,
     Logs,
     FindStringInLogs,
 }
@@ -32,7 +54,13 @@ where
     pub should_quit: bool,
     pub should_query_restart: bool,
     pub group_names: GroupList,
+    pub streams: StreamList,
     pub logs: LogListModel<D>,
+    pub result_table: ResultTableModel,
+    pub stats: StatsModel,
+    pub histogram: HistogramModel,
+    pub detail: Option<DetailModel>,
+    pub context: Option<ContextModel>,
     pub duration: Duration,
     pub query_started: bool,
     pub query_completed: bool,
@@ -40,7 +68,43 @@ where
     pub group_filter_input: InputModel<'a>,
     pub find_string_input: InputModel<'a>,
     pub duration_input: InputModel<'a>,
+    pub export_input: InputModel<'a>,
+    pub show_export: bool,
+    /// Export only the lines matching the find text.
+    pub export_matching_only: bool,
+    /// An existing file the next export to the same path overwrites.
+    pub export_overwrite: Option<PathBuf>,
+    pub presets: PresetList,
+    pub show_presets: bool,
+    pub preset_name_input: InputModel<'a>,
+    pub show_save_preset: bool,
+    /// Also save the preset as a CloudWatch query definition.
+    pub save_preset_to_cloudwatch: bool,
+    config: Config,
+    pub keymap: Keymap,
+    /// The bindings of the focused pane.
+    pub help: Option<HelpModel>,
     pub query_id: Option<QueryId>,
+    /// The start of the current query, while it is in flight.
+    query_request_id: Option<usize>,
+    next_query_request_id: usize,
+    pub search_mode: SearchMode,
+    pub theme: Theme,
+    pub stream_prefix: Option<String>,
+    pub filter_request_id: Option<usize>,
+    next_filter_request_id: usize,
+    /// Filter mode searches stop after this many events.
+    max_events: usize,
+    /// Events shown by the current filter request.
+    filter_events: usize,
+    pub live_tail: Option<LiveTail>,
+    pub notifications: Notifications,
+    pub show_notifications: bool,
+    pub query_progress: Option<QueryProgress>,
+    /// When to ask again for the results of the running query.
+    poll_query_at: Option<Instant>,
+    /// `@ptr`s of the rows already shown, as every poll returns the rows matched so far.
+    seen_ptrs: HashSet<String>,
 }
 
 pub trait Dispatcher: Sized {
@@ -53,26 +117,63 @@ pub trait Dispatcher: Sized {
 pub enum Message {
     Tick,
     KeyInput(KeyEvent),
+    /// Text pasted into the terminal, with bracketed paste.
+    Paste(String),
     GetQueryResultsRequest(QueryId),
-    GetQueryResultsComplete(Vec<SearchResultItem>),
-    StartQueryRequest(StartQueryInput),
-    StartQueryComplete(QueryId),
+    GetQueryResultsRunning(QueryId, Vec<SearchResultItem>, QueryStatistics),
+    GetQueryResultsComplete(QueryId, Vec<SearchResultItem>, QueryStatistics),
+    QueryFailed(QueryId, String),
+    StartQueryRequest(usize, StartQueryInput),
+    StartQueryComplete(usize, QueryId),
+    StartQueryFailed(usize, String),
     StopQueryRequest(QueryId),
+    FilterLogsRequest(usize, FilterLogsInput),
+    /// A page of events, and the input to fetch the next one with.
+    FilterLogsRunning(usize, Vec<FilterOutputItem>, FilterLogsInput),
+    FilterLogsComplete(usize, Vec<FilterOutputItem>),
+    FilterLogsFailed(usize, String),
+    GetStreamsRequest(String, usize),
+    GetStreamsComplete(String, Vec<LogStream>),
+    GetStreamsFailed(String, String),
+    GetLogEventsRequest(LogEventsInput),
+    GetLogEventsComplete(LogEventsInput, LogEventsOutput),
+    GetLogEventsFailed(LogEventsInput, String),
+    GetQueryDefinitionsRequest,
+    GetQueryDefinitionsComplete(Vec<QueryDefinition>),
+    GetQueryDefinitionsFailed(String),
+    PutQueryDefinitionRequest(QueryDefinition),
+    Notify(Notification),
     UpdateLogListStartIndex(usize),
     UpdateLogListEndIndex(usize),
 }
 
 impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
-    pub fn new(dispatcher: D, group_names: Vec<String>, opt: Opt) -> App<'a, D> {
+    pub fn new(dispatcher: D, group_names: Vec<String>, opt: Opt, config: Config) -> App<'a, D> {
+        let theme = config.theme;
+        let keymap = config.keys.clone();
+        let history_dir = crate::get_app_cache_path()
+            .map(|dir| dir.join("history"))
+            .ok();
+        let history = |name: &str| {
+            let path = history_dir
+                .as_ref()
+                .map(|dir| dir.join(format!("{}.jsonl", name)));
+            History::load(path).unwrap_or_else(|e| {
+                log::warn!("{:#}", e);
+                History::default()
+            })
+        };
         let mut default_query_input = InputModel::new()
             .set_placeholder("Filter your logs")
-            .set_value(opt.filter);
+            .set_value(opt.filter())
+            .history(history("filter"));
         default_query_input.focus();
 
-        let group_name_filter = opt.group_name.unwrap_or_default();
+        let group_name_filter = opt.group_name.clone().unwrap_or_default();
         let group_filter_input = InputModel::new()
             .set_placeholder("Filter log groups")
             .set_value(group_name_filter.clone())
+            .history(history("group_filter"))
             .block(
                 Block::default()
                     .borders(Borders::BOTTOM)
@@ -82,31 +183,32 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
 
         let find_string_input = InputModel::new()
             .set_placeholder("Find string in logs")
+            .history(history("find"))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::DarkGray)),
+                    .border_style(Style::default().fg(theme.inactive_border)),
             )
             .focused_block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::White)),
+                    .border_style(Style::default().fg(theme.border)),
             );
 
-        let duration = Duration::from_opt(&opt.since, opt.end.as_deref());
-        let duration_input_value = if opt.end.is_none() {
-            opt.since
-        } else {
-            format!("{} - {}", opt.since, opt.end.unwrap())
+        let duration = Duration::from_opt(opt.since(), opt.end.as_deref());
+        let duration_input_value = match opt.end {
+            Some(ref end) => format!("{} - {}", opt.since(), end),
+            None => opt.since().to_owned(),
         };
 
         let duration_input = InputModel::new()
-            .set_placeholder("duration(default 15m)")
+            .set_placeholder("duration(default 15m, or live)")
             .set_value(duration_input_value)
+            .history(history("duration"))
             .block(
                 Block::default()
                     .borders(Borders::LEFT)
-                    .border_style(Style::default().fg(Color::DarkGray)),
+                    .border_style(Style::default().fg(theme.inactive_border)),
             )
             .focused_block(
                 Block::default()
@@ -120,72 +222,776 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
             should_quit: false,
             should_query_restart: false,
             group_names,
+            streams: StreamList::new(),
             logs: LogListModel::new(dispatcher.clone()),
+            result_table: ResultTableModel::new(),
+            stats: StatsModel::new(),
+            histogram: HistogramModel::new(),
+            detail: None,
+            context: None,
             dispatcher,
             duration,
             query_id: None,
+            query_request_id: None,
+            next_query_request_id: 0,
+            search_mode: opt.mode(),
+            theme,
+            max_events: opt.max_events(),
+            stream_prefix: opt.stream_prefix,
+            filter_request_id: None,
+            next_filter_request_id: 0,
+            filter_events: 0,
+            live_tail: None,
+            notifications: Notifications::new(),
+            show_notifications: false,
+            query_progress: None,
+            poll_query_at: None,
+            seen_ptrs: HashSet::new(),
             query_started: false,
             query_completed: false,
             default_query_input,
             duration_input,
             group_filter_input,
             find_string_input,
+            export_input: InputModel::new().set_placeholder("Path to export to"),
+            show_export: false,
+            export_matching_only: false,
+            export_overwrite: None,
+            presets: PresetList::new(),
+            show_presets: false,
+            preset_name_input: InputModel::new().set_placeholder("Preset name"),
+            show_save_preset: false,
+            save_preset_to_cloudwatch: false,
+            config,
+            keymap,
+            help: None,
         }
     }
 
     pub fn on_key(&mut self, k: KeyEvent) -> Result<()> {
-        match k {
-            KeyEvent {
-                code: KeyCode::Tab,
-                modifiers: KeyModifiers::NONE,
-            } => self.focus_next(),
-            KeyEvent {
-                code: KeyCode::Tab,
-                modifiers: KeyModifiers::CONTROL,
-            } => self.focus_prev(),
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-            } => match self.focus_state {
-                FocusTarget::LogFilter => {
-                    self.should_query_restart = true;
-                    self.request_stop_query();
-                    self.logs.clear();
+        let contexts = self.key_contexts(&k);
+        match self.keymap.find(contexts, &k) {
+            Some(Action::Help) if self.help.is_none() => self.open_help(contexts),
+            Some(action) => self.on_action(action),
+            None => {
+                if let Some(c) = keymap::text(&k) {
+                    self.edit_input(|input| input.on_text(c));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Contexts whose bindings the key is looked up in, in order, for what has the focus.
+    fn key_contexts(&self, k: &KeyEvent) -> &'static [Context] {
+        // Characters are typed, even when a global action is bound to them.
+        let typed = keymap::text(k).is_some();
+        let searching = self.focused_input().map(|i| i.is_searching()) == Some(true);
+        if self.help.is_some() || self.show_notifications || self.show_presets {
+            &[Context::List, Context::Global]
+        } else if self.detail.is_some() || self.context.is_some() {
+            &[Context::Logs, Context::List, Context::Global]
+        } else if self.show_export || self.show_save_preset || searching {
+            if typed {
+                &[Context::Input]
+            } else {
+                &[Context::Input, Context::Global]
+            }
+        } else {
+            match self.focused_input() {
+                Some(_) if typed => &[Context::Input],
+                Some(_) => &[Context::Global, Context::Input],
+                None if self.focus_state == FocusTarget::Logs => {
+                    &[Context::Global, Context::Logs, Context::List]
+                }
+                None => &[Context::Global, Context::List],
+            }
+        }
+    }
+
+    fn focused_input(&self) -> Option<&InputModel<'a>> {
+        match self.focus_state {
+            FocusTarget::LogFilter => Some(&self.default_query_input),
+            FocusTarget::Duration => Some(&self.duration_input),
+            FocusTarget::GroupFilter => Some(&self.group_filter_input),
+            FocusTarget::FindStringInLogs => Some(&self.find_string_input),
+            _ => None,
+        }
+    }
+
+    fn on_action(&mut self, action: Action) {
+        if let Some(ref mut help) = self.help {
+            match action {
+                Action::Close | Action::Help => self.help = None,
+                _ => help.on_action(action),
+            }
+            return;
+        }
+        if let Some(ref mut detail) = self.detail {
+            match action {
+                Action::Close => self.detail = None,
+                Action::OpenContext => {
+                    self.detail = None;
+                    self.open_context();
+                }
+                Action::Copy => {
+                    if let Some(value) = detail.cursor_value().map(str::to_owned) {
+                        self.copy_to_clipboard("the value", &value);
+                    }
                 }
-                FocusTarget::Duration => {
-                    let duration: Duration = self.duration_input.value().into();
-                    if duration.is_valid() {
-                        // TODO: error handling
-                        self.duration = duration;
+                _ => detail.on_action(action),
+            }
+            return;
+        }
+        if let Some(ref mut context) = self.context {
+            match action {
+                Action::Close => self.context = None,
+                _ => context.on_action(action),
+            }
+            return;
+        }
+        if self.show_notifications {
+            self.on_notifications_action(action);
+            return;
+        }
+        if self.show_export {
+            self.on_export_action(action);
+            return;
+        }
+        if self.show_presets {
+            self.on_presets_action(action);
+            return;
+        }
+        if self.show_save_preset {
+            self.on_save_preset_action(action);
+            return;
+        }
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::FocusNext => self.focus_next(),
+            Action::FocusPrev => self.focus_prev(),
+            Action::Notifications => {
+                self.notifications.select_latest();
+                self.notifications.dismiss();
+                self.show_notifications = true;
+            }
+            Action::Export => self.open_export(),
+            Action::SavePreset => {
+                self.preset_name_input.focus();
+                self.show_save_preset = true;
+            }
+            Action::OpenPresets => {
+                self.presets.open(&self.config.presets);
+                self.show_presets = true;
+                self.dispatcher
+                    .dispatch(Message::GetQueryDefinitionsRequest);
+            }
+            Action::SwitchMode => {
+                self.search_mode = self.search_mode.next();
+                self.notify(Notification::info(format!(
+                    "search mode: {}",
+                    self.search_mode
+                )));
+                self.should_query_restart = true;
+                self.request_stop_query();
+                self.clear_results();
+            }
+            Action::Submit => self.submit(),
+            _ => match self.focus_state {
+                FocusTarget::Groups => {
+                    self.group_names.on_action(action);
+                    if action == Action::Select {
                         self.should_query_restart = true;
                         self.request_stop_query();
-                        self.logs.clear();
+                        self.clear_results();
                     }
                 }
-                FocusTarget::Groups => {
-                    self.group_names.on_key(k);
+                FocusTarget::Streams => self.on_streams_action(action),
+                FocusTarget::Logs => self.on_logs_action(action),
+                _ => self.edit_input(|input| input.on_action(action)),
+            },
+        }
+    }
+
+    /// Run the query with the value of the focused input, or just remember the value.
+    fn submit(&mut self) {
+        match self.focus_state {
+            FocusTarget::LogFilter => {
+                self.commit_input();
+                self.should_query_restart = true;
+                self.request_stop_query();
+                self.clear_results();
+            }
+            FocusTarget::Duration => {
+                let duration: Duration = self.duration_input.value().into();
+                if duration.is_valid() {
+                    self.commit_input();
+                    self.duration = duration;
+                    self.streams.reload();
                     self.should_query_restart = true;
                     self.request_stop_query();
-                    self.logs.clear();
+                    self.clear_results();
+                } else {
+                    self.notify(Notification::warning(format!(
+                        "invalid duration \"{}\", expected e.g. \"15m\", \"3h - 1h\", \"live\" or RFC3339 times.",
+                        self.duration_input.value()
+                    )));
                 }
-                _ => {}
-            },
-            _ => match self.focus_state {
-                FocusTarget::LogFilter => self.default_query_input.on_key(k),
-                FocusTarget::Duration => self.duration_input.on_key(k),
-                FocusTarget::GroupFilter => {
-                    self.group_filter_input.on_key(k);
-                    self.group_names.set_filter(self.group_filter_input.value());
+            }
+            FocusTarget::GroupFilter | FocusTarget::FindStringInLogs => self.commit_input(),
+            _ => {}
+        }
+    }
+
+    /// Show the bindings of the contexts, the global ones after those of the focused pane.
+    fn open_help(&mut self, contexts: &[Context]) {
+        let mut contexts = contexts.to_vec();
+        contexts.sort_by_key(|c| *c == Context::Global);
+        self.help = Some(HelpModel::new(
+            format!("Keys: {}", self.focus_name()),
+            &self.keymap,
+            &contexts,
+        ));
+    }
+
+    /// What has the focus, for the title of the help.
+    fn focus_name(&self) -> &'static str {
+        if self.detail.is_some() {
+            "detail"
+        } else if self.context.is_some() {
+            "context"
+        } else if self.show_notifications {
+            "notifications"
+        } else if self.show_export {
+            "export"
+        } else if self.show_presets {
+            "presets"
+        } else if self.show_save_preset {
+            "save preset"
+        } else {
+            match self.focus_state {
+                FocusTarget::LogFilter => "log filter",
+                FocusTarget::Duration => "duration",
+                FocusTarget::GroupFilter => "group filter",
+                FocusTarget::Groups => "log groups",
+                FocusTarget::Streams => "log streams",
+                FocusTarget::Logs => "logs",
+                FocusTarget::FindStringInLogs => "find",
+            }
+        }
+    }
+
+    /// Insert pasted text into the input that has the focus.
+    pub fn on_paste(&mut self, text: &str) {
+        self.edit_input(|input| input.insert_str(text));
+    }
+
+    /// Edit the input that has the focus, the one of the prompt when one is open.
+    fn edit_input(&mut self, edit: impl FnOnce(&mut InputModel<'a>)) {
+        if self.show_export {
+            edit(&mut self.export_input);
+            return;
+        }
+        if self.show_save_preset {
+            edit(&mut self.preset_name_input);
+            return;
+        }
+        if self.help.is_some()
+            || self.detail.is_some()
+            || self.context.is_some()
+            || self.show_notifications
+            || self.show_presets
+        {
+            return;
+        }
+        match self.focus_state {
+            FocusTarget::LogFilter => edit(&mut self.default_query_input),
+            FocusTarget::Duration => edit(&mut self.duration_input),
+            FocusTarget::GroupFilter => {
+                edit(&mut self.group_filter_input);
+                self.on_group_filter_changed();
+            }
+            FocusTarget::FindStringInLogs => {
+                edit(&mut self.find_string_input);
+                self.logs.set_find_text(self.find_string_input.value());
+            }
+            _ => {}
+        }
+    }
+
+    fn on_group_filter_changed(&mut self) {
+        if let Err(e) = self.group_names.set_filter(self.group_filter_input.value()) {
+            self.notify(Notification::warning(format!(
+                "invalid group filter: {}",
+                e
+            )));
+        }
+    }
+
+    pub fn clear_results(&mut self) {
+        self.logs.clear();
+        self.result_table.clear();
+        self.stats.clear();
+        self.histogram.clear();
+        self.detail = None;
+        self.context = None;
+        self.seen_ptrs.clear();
+    }
+
+    /// Remember the value of the focused input in its history.
+    fn commit_input(&mut self) {
+        let input = match self.focus_state {
+            FocusTarget::LogFilter => &mut self.default_query_input,
+            FocusTarget::Duration => &mut self.duration_input,
+            FocusTarget::GroupFilter => &mut self.group_filter_input,
+            FocusTarget::FindStringInLogs => &mut self.find_string_input,
+            _ => return,
+        };
+        if let Err(e) = input.commit() {
+            self.notify(Notification::warning(format!(
+                "failed to save the history: {:#}",
+                e
+            )));
+        }
+    }
+
+    pub fn notify(&mut self, notification: Notification) {
+        self.notifications.push(notification);
+    }
+
+    fn on_notifications_action(&mut self, action: Action) {
+        match action {
+            Action::Close | Action::Notifications => self.show_notifications = false,
+            Action::Down => self.notifications.next(),
+            Action::Up => self.notifications.previous(),
+            _ => {}
+        }
+    }
+
+    /// Selecting streams restricts the query to them, so run it again.
+    fn on_streams_action(&mut self, action: Action) {
+        if self.streams.on_action(action) {
+            self.should_query_restart = true;
+            self.request_stop_query();
+            self.clear_results();
+        }
+    }
+
+    fn open_export(&mut self) {
+        if self.logs.items.is_empty() {
+            self.notify(Notification::warning("there are no results to export."));
+            return;
+        }
+        if self.export_input.is_empty() {
+            self.export_input.set_text(format!(
+                "kanten-{}.jsonl",
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            ));
+        }
+        self.export_input.focus();
+        self.show_export = true;
+        self.export_overwrite = None;
+    }
+
+    /// The next-pane key toggles exporting only the matching lines.
+    fn on_export_action(&mut self, action: Action) {
+        match action {
+            Action::Cancel => self.show_export = false,
+            Action::FocusNext => self.export_matching_only = !self.export_matching_only,
+            Action::Submit => self.export(),
+            _ => self.export_input.on_action(action),
+        }
+    }
+
+    /// Lines exported, all of them unless only the ones matching the find text are.
+    fn export_items(&self) -> impl Iterator<Item = &LogListItem> {
+        let find = if self.export_matching_only {
+            self.find_string_input.value()
+        } else {
+            ""
+        };
+        self.logs
+            .items
+            .iter()
+            .filter(move |item| item.contains(find))
+    }
+
+    /// Write the results to the path in the format of its extension. An
+    /// existing file is only overwritten when submitted a second time.
+    fn export(&mut self) {
+        let value = self.export_input.value().trim();
+        if value.is_empty() {
+            return;
+        }
+        let path = match (value.strip_prefix("~/"), dirs_next::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(value),
+        };
+        let format = ExportFormat::from_path(&path);
+        let rows: Vec<Vec<(String, String)>> = self.export_items().map(|i| i.fields()).collect();
+        let file = if self.export_overwrite.as_ref() == Some(&path) {
+            File::create(&path)
+        } else {
+            OpenOptions::new().write(true).create_new(true).open(&path)
+        };
+        if let Err(ref e) = file {
+            if e.kind() == ErrorKind::AlreadyExists {
+                self.notify(Notification::warning(format!(
+                    "{} already exists, press {} again to overwrite it.",
+                    path.display(),
+                    self.keymap.hint(Context::Input, Action::Submit)
+                )));
+                self.export_overwrite = Some(path);
+                return;
+            }
+        }
+        let written = file
+            .map_err(anyhow::Error::from)
+            .and_then(|file| format.write(&mut BufWriter::new(file), &rows));
+        match written {
+            Ok(()) => {
+                self.export_overwrite = None;
+                self.show_export = false;
+                self.export_input.blur();
+                self.notify(Notification::info(format!(
+                    "exported {} events to {} as {}.",
+                    rows.len(),
+                    path.display(),
+                    format
+                )));
+            }
+            Err(e) => self.notify(Notification::error(format!(
+                "failed to export to {}: {:#}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    fn on_presets_action(&mut self, action: Action) {
+        match action {
+            Action::Close => self.show_presets = false,
+            Action::Select => {
+                if let Some(item) = self.presets.selected().cloned() {
+                    self.show_presets = false;
+                    self.apply_preset(&item.preset);
+                    self.notify(Notification::info(format!("loaded preset {}.", item.name)));
                 }
-                FocusTarget::Logs => self.logs.on_key(k),
-                FocusTarget::Groups => self.group_names.on_key(k),
-                FocusTarget::FindStringInLogs => {
-                    self.find_string_input.on_key(k);
-                    self.logs.set_find_text(self.find_string_input.value());
-                } // _ => {}
-            },
+            }
+            _ => self.presets.on_action(action),
+        }
+    }
+
+    /// The next-pane key toggles saving to CloudWatch too.
+    fn on_save_preset_action(&mut self, action: Action) {
+        match action {
+            Action::Cancel => {
+                self.preset_name_input.blur();
+                self.show_save_preset = false;
+            }
+            Action::FocusNext => self.save_preset_to_cloudwatch = !self.save_preset_to_cloudwatch,
+            Action::Submit => self.save_preset(),
+            _ => self.preset_name_input.on_action(action),
+        }
+    }
+
+    /// The filter, duration, mode and selected groups.
+    fn current_preset(&self) -> Preset {
+        let (since, end) = split_range(self.duration_input.value());
+        Preset {
+            since: Some(since.to_owned()).filter(|s| !s.is_empty()),
+            end: end.map(str::to_owned),
+            groups: self.group_names.selected.iter().cloned().collect(),
+            filter: Some(self.default_query_input.value().to_owned()),
+            mode: Some(self.search_mode.to_string()),
+            stream_prefix: self.stream_prefix.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn save_preset(&mut self) {
+        let name = self.preset_name_input.value().trim().to_owned();
+        if name.is_empty() {
+            return;
+        }
+        let preset = self.current_preset();
+        if self.save_preset_to_cloudwatch {
+            match preset.to_definition(&name) {
+                Some(definition) => self
+                    .dispatcher
+                    .dispatch(Message::PutQueryDefinitionRequest(definition)),
+                None => self.notify(Notification::warning(
+                    "filter mode has no query, so the preset isn't saved to CloudWatch Logs.",
+                )),
+            }
+        }
+        match self.config.save_preset(&name, preset) {
+            Ok(path) => {
+                self.show_save_preset = false;
+                self.preset_name_input.blur();
+                self.notify(Notification::info(format!(
+                    "saved preset {} to {}.",
+                    name,
+                    path.display()
+                )));
+            }
+            Err(e) => self.notify(Notification::error(format!(
+                "failed to save preset {}: {:#}",
+                name, e
+            ))),
+        }
+    }
+
+    /// Set what the preset has and run the query again.
+    fn apply_preset(&mut self, preset: &Preset) {
+        if let Some(ref filter) = preset.filter {
+            self.default_query_input.set_text(filter.clone());
+        }
+        if let Some(ref mode) = preset.mode {
+            match mode.parse() {
+                Ok(mode) => self.search_mode = mode,
+                Err(e) => self.notify(Notification::warning(format!("{:#}", e))),
+            }
+        }
+        if let Some(ref since) = preset.since {
+            let value = match preset.end {
+                Some(ref end) => format!("{} - {}", since, end),
+                None => since.clone(),
+            };
+            self.duration = value.as_str().into();
+            self.duration_input.set_text(value);
+        }
+        if let Some(pattern) = preset.group_pattern() {
+            if let Err(e) = self.group_names.select_matching(&pattern) {
+                self.notify(Notification::warning(format!(
+                    "invalid group name {}: {}",
+                    pattern, e
+                )));
+            }
+        }
+        if preset.stream_prefix.is_some() {
+            self.stream_prefix = preset.stream_prefix.clone();
+        }
+        self.streams.reload();
+        self.should_query_restart = true;
+        self.request_stop_query();
+        self.clear_results();
+    }
+
+    fn on_logs_action(&mut self, action: Action) {
+        if self.is_histogram_visible() && self.histogram.on_action(action) {
+            return;
+        }
+        if self.stats.is_active() {
+            self.stats.on_action(action);
+            return;
+        }
+        match action {
+            Action::Zoom if self.is_histogram_visible() => self.zoom_into_selected_bin(),
+            Action::Select => self.open_detail(),
+            Action::OpenContext => self.open_context(),
+            Action::Copy => {
+                if let Some(text) = self.selected_item().map(|item| item.text()) {
+                    self.copy_to_clipboard("the line", &text);
+                }
+            }
+            Action::CopyMessage => {
+                if let Some(message) = self.selected_item().map(|item| item.message().to_owned()) {
+                    self.copy_to_clipboard("the message", &message);
+                }
+            }
+            _ if self.result_table.is_tabular() => {
+                self.result_table.on_action(action, self.logs.items.len())
+            }
+            _ => self.logs.on_action(action),
+        }
+    }
+
+    fn selected_item(&self) -> Option<&LogListItem> {
+        let selected = if self.result_table.is_tabular() {
+            self.result_table.state.selected()
+        } else {
+            self.logs.state.selected()
+        };
+        selected.and_then(|i| self.logs.items.get(i))
+    }
+
+    fn copy_to_clipboard(&mut self, what: &str, text: &str) {
+        match clipboard::copy(text) {
+            Ok(method) => self.notify(Notification::info(format!(
+                "copied {} ({} chars) to the clipboard via {}.",
+                what,
+                text.chars().count(),
+                method
+            ))),
+            Err(e) => self.notify(Notification::error(format!(
+                "failed to copy {}: {:#}",
+                what, e
+            ))),
+        }
+    }
+
+    fn open_detail(&mut self) {
+        if self.stats.is_active() {
+            return;
+        }
+        if let Some(item) = self.selected_item() {
+            self.detail = Some(DetailModel::new(item));
+        }
+    }
+
+    /// Fetch the events around the selected one in its stream.
+    fn open_context(&mut self) {
+        let item = match self.selected_item() {
+            Some(item) => item,
+            None => return,
+        };
+        // Logs Insights prefixes `@log` with the account id.
+        let group = item
+            .field("@log")
+            .and_then(|log| log.rsplit(':').next())
+            .map(str::to_owned);
+        let stream = item.field("@logStream").map(str::to_owned);
+        let timestamp = parse_timestamp(item.timestamp());
+        match (group, stream, timestamp) {
+            (Some(group), Some(stream), Some(timestamp)) => {
+                let input = LogEventsInput {
+                    group,
+                    stream,
+                    timestamp,
+                    limit: CONTEXT_EVENTS,
+                };
+                self.context = Some(ContextModel::new(input.clone(), item.message()));
+                self.dispatcher
+                    .dispatch(Message::GetLogEventsRequest(input));
+            }
+            _ => self.notify(Notification::warning(
+                "the context needs the @log, @logStream and @timestamp fields of the event.",
+            )),
+        }
+    }
+
+    pub fn is_histogram_visible(&self) -> bool {
+        self.histogram.is_visible() && !self.stats.is_active()
+    }
+
+    /// Narrow the duration to the selected histogram bucket and run the query again.
+    fn zoom_into_selected_bin(&mut self) {
+        if let Some((start, end)) = self.histogram.selected_range() {
+            let format = |secs: i64| {
+                chrono::Utc
+                    .timestamp_opt(secs, 0)
+                    .single()
+                    .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+                    .unwrap_or_default()
+            };
+            self.duration_input
+                .set_text(format!("{} - {}", format(start), format(end)));
+            self.duration = Duration::Duration {
+                start: Some(start),
+                end: Some(end),
+            };
+            self.should_query_restart = true;
+            self.request_stop_query();
+            self.clear_results();
+        }
+    }
+
+    /// Show FilterLogEvents results, appended while tailing and newest first otherwise.
+    fn push_results(&mut self, items: Vec<SearchResultItem>) {
+        let items: Vec<LogListItem> = items
+            .into_iter()
+            .map(|item| {
+                LogListItem::new(item.timestamp().to_owned(), item.message().to_owned())
+                    .set_fields(item.fields)
+            })
+            .collect();
+        self.result_table.update_columns(&items);
+        self.histogram.push(&items);
+        for item in items {
+            if self.live_tail.is_some() {
+                self.logs.push(item);
+            } else {
+                // Pages of different groups overlap in time.
+                let index = self
+                    .logs
+                    .items
+                    .partition_point(|i| i.timestamp() >= item.timestamp());
+                self.logs.insert(index, item);
+            }
+        }
+    }
+
+    /// Show a page of FilterLogEvents results and fetch the next one, until
+    /// `--max-events` are shown.
+    fn on_filter_page(
+        &mut self,
+        id: usize,
+        mut items: Vec<FilterOutputItem>,
+        next: Option<FilterLogsInput>,
+    ) {
+        let left = self.max_events.saturating_sub(self.filter_events);
+        let truncated = items.len() > left || (items.len() == left && next.is_some());
+        items.truncate(left);
+        self.filter_events += items.len();
+        let items = match self.live_tail {
+            Some(ref mut tail) => tail.accept(items),
+            None => items,
+        };
+        self.push_results(items.into_iter().map(SearchResultItem::from).collect());
+        match next {
+            Some(input) if !truncated => self
+                .dispatcher
+                .dispatch(Message::FilterLogsRequest(id, input)),
+            _ => {
+                if truncated {
+                    self.notify(Notification::warning(format!(
+                        "stopped after {} events, narrow the search or raise --max-events.",
+                        self.filter_events
+                    )));
+                }
+                if let Some(ref mut tail) = self.live_tail {
+                    tail.finish();
+                }
+                self.query_completed = true;
+                self.loading = false;
+                self.filter_request_id = None;
+            }
+        }
+    }
+
+    /// Show the rows of a (possibly still running) Logs Insights query that aren't shown yet.
+    fn merge_query_results(&mut self, items: Vec<SearchResultItem>) {
+        if StatsModel::is_aggregate(&items) {
+            // Aggregates are recomputed on every poll, so just replace them.
+            self.stats.set_items(&items);
+            return;
+        }
+        let items: Vec<LogListItem> = items
+            .into_iter()
+            .filter(|item| match item.get("@ptr") {
+                Some(ptr) => self.seen_ptrs.insert(ptr.to_owned()),
+                None => true,
+            })
+            .map(|item| {
+                LogListItem::new(item.timestamp().to_owned(), item.message().to_owned())
+                    .set_fields(item.fields)
+            })
+            .collect();
+        self.result_table.update_columns(&items);
+        self.histogram.push(&items);
+        for item in items {
+            // The default query sorts newest first, keep that order across polls.
+            if self.search_mode == SearchMode::Insights {
+                let index = self
+                    .logs
+                    .items
+                    .partition_point(|i| i.timestamp() >= item.timestamp());
+                self.logs.insert(index, item);
+            } else {
+                self.logs.push(item);
+            }
         }
-        Ok(())
     }
 
     pub fn request_stop_query(&mut self) {
@@ -195,30 +1001,143 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
                 .dispatch(Message::StopQueryRequest(id.clone()));
             self.query_id = None;
         }
+        // A start in flight is stopped once it completes.
+        self.query_request_id = None;
+        self.poll_query_at = None;
+        // FilterLogEvents can't be cancelled, so just ignore its results.
+        self.filter_request_id = None;
     }
 
     pub async fn on_tick(&mut self) {
+        self.load_streams();
+
+        if self.poll_query_at.map(|at| at <= Instant::now()) == Some(true) {
+            self.poll_query_at = None;
+            if let Some(ref query_id) = self.query_id {
+                self.dispatcher
+                    .dispatch(Message::GetQueryResultsRequest(query_id.clone()));
+            }
+        }
+
         if !self.query_started || self.should_query_restart {
             log::trace!("restart query");
             self.should_query_restart = false;
+            self.live_tail = None;
+            self.query_progress = None;
+            self.logs.set_tailing(false);
+            self.histogram.set_range(match self.duration {
+                Duration::Duration {
+                    start: Some(start),
+                    end: Some(end),
+                } => Some((start, end)),
+                _ => None,
+            });
 
             let groups: Vec<String> = self.group_names.selected.clone().into_iter().collect();
             if !groups.is_empty() && self.duration.is_valid() {
-                let Duration::Duration { start, end } = self.duration;
                 self.query_started = true;
                 self.loading = true;
-                self.dispatcher
-                    .dispatch(Message::StartQueryRequest(StartQueryInput {
-                        start: start.unwrap(),
-                        end: end.unwrap(),
-                        filter: self.default_query_input.value().to_string(),
-                        groups,
-                    }));
+                match self.duration {
+                    Duration::Live => {
+                        self.live_tail = Some(LiveTail::new());
+                        self.logs.set_tailing(true);
+                    }
+                    Duration::Duration { start, end } => match self.search_mode {
+                        SearchMode::Insights | SearchMode::Query => {
+                            let query = if self.search_mode == SearchMode::Query {
+                                self.default_query_input.value().to_string()
+                            } else {
+                                default_query(self.default_query_input.value())
+                            };
+                            self.query_progress = Some(QueryProgress::new());
+                            self.request_start_query(StartQueryInput {
+                                start: start.unwrap(),
+                                end: end.unwrap(),
+                                query,
+                                stream_names: self.streams.stream_names(&groups),
+                                groups,
+                            })
+                        }
+                        SearchMode::Filter => {
+                            let input = FilterLogsInput {
+                                start: start.unwrap(),
+                                end: end.unwrap(),
+                                stream_names: self.streams.stream_names(&groups),
+                                groups,
+                                stream_name_prefix: self.stream_prefix.clone(),
+                                filter_pattern: self.default_query_input.value().to_string(),
+                                cursor: FilterLogsCursor::default(),
+                            };
+                            self.request_filter_logs(input);
+                        }
+                    },
+                }
+            }
+        }
+
+        if self.live_tail.as_ref().map(|t| t.should_poll()) == Some(true) {
+            let groups: Vec<String> = self.group_names.selected.clone().into_iter().collect();
+            let filter_pattern = self.default_query_input.value().to_string();
+            let stream_prefix = self.stream_prefix.clone();
+            let stream_names = self.streams.stream_names(&groups);
+            if let Some(ref mut tail) = self.live_tail {
+                let input = tail.poll(groups, stream_prefix, stream_names, filter_pattern);
+                self.request_filter_logs(input);
             }
         }
     }
 
+    /// List the streams of the highlighted group once the highlight settles on it.
+    fn load_streams(&mut self) {
+        let group = self
+            .group_names
+            .state
+            .selected()
+            .and_then(|i| self.group_names.filtered.get(i))
+            .cloned();
+        self.streams.set_group(group);
+        if let Some(group) = self.streams.take_due() {
+            let since = match self.duration {
+                Duration::Duration { start, .. } => start.unwrap_or_default() * 1000,
+                Duration::Live => (chrono::Local::now().timestamp() - LIVE_STREAMS_SECS) * 1000,
+            };
+            self.dispatcher
+                .dispatch(Message::GetStreamsRequest(group, since.max(0) as usize));
+        }
+    }
+
+    fn request_start_query(&mut self, input: StartQueryInput) {
+        let id = self.next_query_request_id;
+        self.next_query_request_id += 1;
+        self.query_request_id = Some(id);
+        self.dispatcher
+            .dispatch(Message::StartQueryRequest(id, input));
+    }
+
+    fn on_query_failed(&mut self, e: String) {
+        self.notify(Notification::error(e));
+        self.query_completed = true;
+        self.loading = false;
+        self.query_progress = None;
+    }
+
+    fn request_filter_logs(&mut self, input: FilterLogsInput) {
+        let id = self.next_filter_request_id;
+        self.next_filter_request_id += 1;
+        self.filter_request_id = Some(id);
+        self.filter_events = 0;
+        self.dispatcher
+            .dispatch(Message::FilterLogsRequest(id, input));
+    }
+
     fn blur_all(&mut self) {
+        // These have nothing to run on Enter, so their values are remembered when leaving them.
+        if matches!(
+            self.focus_state,
+            FocusTarget::GroupFilter | FocusTarget::FindStringInLogs
+        ) {
+            self.commit_input();
+        }
         self.default_query_input.blur();
         self.duration_input.blur();
         self.group_filter_input.blur();
@@ -239,7 +1158,8 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
             FocusTarget::GroupFilter => {
                 self.focus_state = FocusTarget::Groups;
             }
-            FocusTarget::Groups => self.focus_state = FocusTarget::Logs,
+            FocusTarget::Groups => self.focus_state = FocusTarget::Streams,
+            FocusTarget::Streams => self.focus_state = FocusTarget::Logs,
             FocusTarget::Logs => {
                 self.find_string_input.focus();
                 self.focus_state = FocusTarget::FindStringInLogs;
@@ -270,9 +1190,12 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
                 self.group_filter_input.focus();
                 self.focus_state = FocusTarget::GroupFilter;
             }
-            FocusTarget::Logs => {
+            FocusTarget::Streams => {
                 self.focus_state = FocusTarget::Groups;
             }
+            FocusTarget::Logs => {
+                self.focus_state = FocusTarget::Streams;
+            }
             FocusTarget::FindStringInLogs => {
                 self.focus_state = FocusTarget::Logs;
             }
@@ -282,23 +1205,110 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
     pub async fn update(&mut self, message: Message) {
         log::trace!("update message {:?}", message);
         match message {
-            Message::GetQueryResultsComplete(items) => {
-                for item in items {
-                    self.logs
-                        .push(LogListItem::new(item.timestamp, item.message))
+            Message::GetQueryResultsComplete(query_id, items, statistics)
+                if self.query_id.as_ref() == Some(&query_id) =>
+            {
+                if let Some(ref mut progress) = self.query_progress {
+                    progress.finish(statistics);
+                }
+                if statistics.slices > 1 {
+                    // Without a `sort @timestamp`, the rows can't be sorted again across the queries.
+                    let note = if self.search_mode == SearchMode::Query
+                        && timestamp_sort(self.default_query_input.value()).is_none()
+                    {
+                        " The query's sort and limit applied to each of them."
+                    } else {
+                        ""
+                    };
+                    self.notify(Notification::info(format!(
+                        "results were stitched together from {} queries (over {} rows or more than 20 log groups).{}",
+                        statistics.slices, DEFAULT_LIMIT, note
+                    )));
                 }
+                self.merge_query_results(items);
                 self.query_completed = true;
                 self.loading = false;
                 self.query_id = None;
             }
-            Message::StartQueryComplete(query_id) => {
+            // Keep polling unless the query was stopped meanwhile.
+            Message::GetQueryResultsRunning(query_id, items, statistics)
+                if self.query_id.as_ref() == Some(&query_id) =>
+            {
+                if let Some(ref mut progress) = self.query_progress {
+                    progress.update(statistics);
+                }
+                self.merge_query_results(items);
+                // On a tick, so that other requests aren't held up meanwhile.
+                self.poll_query_at = Some(Instant::now() + crate::QUERY_POLL_INTERVAL);
+            }
+            // Ignore failures of a query that was stopped or replaced meanwhile.
+            Message::QueryFailed(query_id, e) if self.query_id.as_ref() == Some(&query_id) => {
+                self.query_id = None;
+                self.on_query_failed(e);
+            }
+            Message::StartQueryFailed(id, e) if self.query_request_id == Some(id) => {
+                self.query_request_id = None;
+                self.on_query_failed(e);
+            }
+            Message::FilterLogsFailed(id, e) => {
+                if self.filter_request_id != Some(id) {
+                    return;
+                }
+                if let Some(ref mut tail) = self.live_tail {
+                    tail.finish();
+                }
+                self.notify(Notification::error(e));
+                self.query_completed = true;
+                self.loading = false;
+                self.filter_request_id = None;
+            }
+            Message::Notify(notification) => self.notify(notification),
+            Message::Paste(text) => self.on_paste(&text),
+            Message::GetLogEventsComplete(input, output) => {
+                if let Some(ref mut context) = self.context {
+                    if context.input == input {
+                        context.set_events(output);
+                    }
+                }
+            }
+            Message::GetLogEventsFailed(input, e)
+                if self.context.as_ref().map(|c| &c.input) == Some(&input) =>
+            {
+                self.context = None;
+                self.notify(Notification::error(e));
+            }
+            Message::GetQueryDefinitionsComplete(definitions) => {
+                self.presets.set_definitions(definitions)
+            }
+            Message::GetQueryDefinitionsFailed(e) => {
+                self.presets.loading = false;
+                self.notify(Notification::warning(e));
+            }
+            Message::GetStreamsComplete(group, items) => self.streams.set_items(&group, items),
+            Message::GetStreamsFailed(group, e) => {
+                self.streams.fail(&group);
+                self.notify(Notification::warning(e));
+            }
+            Message::FilterLogsRunning(id, items, input) if self.filter_request_id == Some(id) => {
+                self.on_filter_page(id, items, Some(input))
+            }
+            Message::FilterLogsComplete(id, items) if self.filter_request_id == Some(id) => {
+                self.on_filter_page(id, items, None)
+            }
+            Message::StartQueryComplete(id, query_id) if self.query_request_id == Some(id) => {
                 log::trace!("StartQueryComplete");
+                self.query_request_id = None;
                 self.query_id = Some(query_id.clone());
                 self.query_started = true;
                 self.dispatcher
                     .dispatch(Message::GetQueryResultsRequest(query_id));
                 self.query_completed = false;
             }
+            // The query was stopped or replaced while it started, so it would only take a slot.
+            Message::StartQueryComplete(_, query_id) => {
+                self.dispatcher
+                    .dispatch(Message::StopQueryRequest(query_id));
+            }
             Message::Tick => {
                 self.on_tick().await;
             }
@@ -312,3 +1322,99 @@ impl<'a, D: Dispatcher<Message = Message> + Clone> App<'a, D> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use std::{cell::RefCell, collections::HashMap, rc::Rc};
+    use structopt::StructOpt;
+
+    /// Keeps the dispatched messages instead of sending them to the service.
+    #[derive(Debug, Default, Clone)]
+    struct Recorder(Rc<RefCell<Vec<Message>>>);
+
+    impl Recorder {
+        fn take(&self) -> Vec<Message> {
+            self.0.borrow_mut().drain(..).collect()
+        }
+    }
+
+    impl Dispatcher for Recorder {
+        type Message = Message;
+
+        fn dispatch(&self, message: Message) {
+            self.0.borrow_mut().push(message);
+        }
+    }
+
+    fn app(config: &str) -> (App<'static, Recorder>, Recorder) {
+        let recorder = Recorder::default();
+        let opt = Opt::from_iter(&["kanten", "-s", "1h", "-g", "/app"]);
+        let config: Config = toml::from_str(config).unwrap();
+        let app = App::new(recorder.clone(), vec!["/app".to_owned()], opt, config);
+        (app, recorder)
+    }
+
+    fn start_query_input() -> StartQueryInput {
+        StartQueryInput {
+            start: 0,
+            end: 10,
+            query: "fields @message".to_owned(),
+            groups: vec!["/app".to_owned()],
+            stream_names: HashMap::new(),
+        }
+    }
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn types_keys_bound_to_global_actions_into_prompts() {
+        let (mut app, _) = app("[keys]\npreset = \"vim\"");
+        app.show_export = true;
+        app.export_input.focus();
+        for c in "queue.jsonl".chars() {
+            app.on_key(key(c)).unwrap();
+        }
+        assert_eq!(app.export_input.value(), "queue.jsonl");
+        assert!(!app.should_quit);
+
+        app.on_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        assert!(!app.show_export);
+        app.focus_state = FocusTarget::Logs;
+        app.on_key(key('q')).unwrap();
+        assert!(app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn stops_queries_that_started_after_they_were_replaced() {
+        let (mut app, recorder) = app("");
+        app.request_start_query(start_query_input());
+        app.request_stop_query();
+        app.request_start_query(start_query_input());
+        recorder.take();
+
+        app.update(Message::StartQueryComplete(0, QueryId::new("old")))
+            .await;
+        assert_eq!(app.query_id, None);
+        assert!(matches!(
+            recorder.take().as_slice(),
+            [Message::StopQueryRequest(id)] if id == &QueryId::new("old")
+        ));
+
+        app.update(Message::StartQueryFailed(0, "failed.".to_owned()))
+            .await;
+        assert!(app.notifications.current().is_none());
+
+        app.update(Message::StartQueryComplete(1, QueryId::new("new")))
+            .await;
+        assert_eq!(app.query_id, Some(QueryId::new("new")));
+        assert!(matches!(
+            recorder.take().as_slice(),
+            [Message::GetQueryResultsRequest(id)] if id == &QueryId::new("new")
+        ));
+    }
+}
@@ -1,12 +1,14 @@
 use crate::app::{app::FocusTarget, App, Dispatcher, Message};
 use crate::components::*;
+use crate::keymap::{Action, Context};
+use crate::models::{Duration, ExportFormat, Level, SearchMode};
 
 use tui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
@@ -26,6 +28,312 @@ where
         .split(vertical[1]);
     draw_body(f, app, horizontal[0]);
     draw_status(f, app, horizontal[1]);
+    draw_detail(f, app, horizontal[0]);
+    draw_context(f, app, horizontal[0]);
+    draw_export(f, app, horizontal[0]);
+    draw_presets(f, app, horizontal[0]);
+    draw_save_preset(f, app, horizontal[0]);
+    draw_notifications(f, app, horizontal[0]);
+    draw_help(f, app, horizontal[0]);
+}
+
+fn draw_help<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
+where
+    B: Backend,
+{
+    let help = match app.help {
+        Some(ref mut help) => help,
+        None => return,
+    };
+    let title = format!(
+        "{} ({}: close)",
+        help.title,
+        app.keymap.hint(Context::List, Action::Close)
+    );
+    let view = HelpView::new(help).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(app.theme.border)),
+    );
+    view.draw(
+        f,
+        area.inner(&Margin {
+            vertical: 1,
+            horizontal: 2,
+        }),
+    );
+}
+
+fn draw_detail<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
+where
+    B: Backend,
+{
+    let detail = match app.detail {
+        Some(ref mut detail) => detail,
+        None => return,
+    };
+    let close = app.keymap.hint(Context::List, Action::Close);
+    let title = if detail.is_json() {
+        format!(
+            "Detail ({}: fold/unfold, {}: close)",
+            app.keymap.hint(Context::List, Action::Select),
+            close
+        )
+    } else {
+        format!("Detail ({}: close)", close)
+    };
+    let view = DetailView::new(detail)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(app.theme.border)),
+        )
+        .highlight_style(Style::default().bg(app.theme.highlight));
+    view.draw(
+        f,
+        area.inner(&Margin {
+            vertical: 1,
+            horizontal: 2,
+        }),
+    );
+}
+
+fn draw_context<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
+where
+    B: Backend,
+{
+    let context = match app.context {
+        Some(ref mut context) => context,
+        None => return,
+    };
+    let title = format!(
+        "{} / {} ({}: back to the event, {}: close)",
+        context.input.group,
+        context.input.stream,
+        app.keymap.hint(Context::Logs, Action::BackToEvent),
+        app.keymap.hint(Context::List, Action::Close)
+    );
+    let view = ContextView::new(context)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(app.theme.border)),
+        )
+        .highlight_style(Style::default().bg(app.theme.highlight));
+    view.draw(
+        f,
+        area.inner(&Margin {
+            vertical: 1,
+            horizontal: 2,
+        }),
+    );
+}
+
+fn draw_export<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
+where
+    B: Backend,
+{
+    if !app.show_export {
+        return;
+    }
+    let width = area.width.saturating_sub(4).min(100);
+    let area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(5) / 2,
+        width,
+        height: area.height.min(5),
+    };
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Export ({}: save, {}: toggle filter, {}: cancel)",
+            app.keymap.hint(Context::Input, Action::Submit),
+            app.keymap.hint(Context::Global, Action::FocusNext),
+            app.keymap.hint(Context::Input, Action::Cancel)
+        ))
+        .border_style(Style::default().fg(app.theme.border));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .direction(Direction::Vertical)
+        .split(inner);
+    InputView::new(&app.export_input).draw(f, chunks[0]);
+
+    let find = app.find_string_input.value();
+    let count = app
+        .logs
+        .items
+        .iter()
+        .filter(|item| !app.export_matching_only || item.contains(find))
+        .count();
+    let mut line = Checkbox::from(app.export_matching_only).render();
+    line.0.extend(vec![
+        Span::raw(" only lines matching the find text "),
+        Span::styled(
+            if find.is_empty() {
+                "(none)".to_owned()
+            } else {
+                format!("\"{}\"", find)
+            },
+            Style::default().fg(app.theme.muted),
+        ),
+        Span::styled(
+            format!(
+                "  {} events as {} (.jsonl, .csv or text)",
+                count,
+                ExportFormat::from_path(std::path::Path::new(app.export_input.value()))
+            ),
+            Style::default().fg(app.theme.muted),
+        ),
+    ]);
+    f.render_widget(Paragraph::new(line), chunks[1]);
+}
+
+fn draw_presets<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
+where
+    B: Backend,
+{
+    if !app.show_presets {
+        return;
+    }
+    let items: Vec<ListItem> = app
+        .presets
+        .items
+        .iter()
+        .map(|item| {
+            let preset = &item.preset;
+            let source = match item.source {
+                PresetSource::Local => "local",
+                PresetSource::CloudWatch => "cloudwatch",
+            };
+            let duration = match (preset.since.as_deref(), preset.end.as_deref()) {
+                (Some(since), Some(end)) => format!(" {} - {}", since, end),
+                (Some(since), None) => format!(" {}", since),
+                _ => String::new(),
+            };
+            let groups = if preset.groups.is_empty() {
+                preset.group_name.clone().unwrap_or_default()
+            } else {
+                format!("{} groups", preset.groups.len())
+            };
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!("{:<10} ", source),
+                    Style::default().fg(app.theme.muted),
+                ),
+                Span::raw(item.name.as_str()),
+                Span::styled(
+                    format!(
+                        "  {}{}  {}  {}",
+                        preset.mode.as_deref().unwrap_or_default(),
+                        duration,
+                        groups,
+                        preset.filter.as_deref().unwrap_or_default()
+                    ),
+                    Style::default().fg(app.theme.muted),
+                ),
+            ]))
+        })
+        .collect();
+    let title = if app.presets.loading {
+        "Presets (loading CloudWatch query definitions...)".to_owned()
+    } else if app.presets.items.is_empty() {
+        format!(
+            "Presets (none yet, {} saves the current search)",
+            app.keymap.hint(Context::Global, Action::SavePreset)
+        )
+    } else {
+        format!(
+            "Presets ({}: load, {}: close)",
+            app.keymap.hint(Context::List, Action::Select),
+            app.keymap.hint(Context::List, Action::Close)
+        )
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(app.theme.border)),
+        )
+        .highlight_style(Style::default().bg(app.theme.highlight));
+    let area = area.inner(&Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut app.presets.state);
+}
+
+fn draw_save_preset<B, D: Dispatcher<Message = Message>>(
+    f: &mut Frame<B>,
+    app: &mut App<D>,
+    area: Rect,
+) where
+    B: Backend,
+{
+    if !app.show_save_preset {
+        return;
+    }
+    let width = area.width.saturating_sub(4).min(100);
+    let area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(5) / 2,
+        width,
+        height: area.height.min(5),
+    };
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Save preset ({}: save, {}: toggle CloudWatch, {}: cancel)",
+            app.keymap.hint(Context::Input, Action::Submit),
+            app.keymap.hint(Context::Global, Action::FocusNext),
+            app.keymap.hint(Context::Input, Action::Cancel)
+        ))
+        .border_style(Style::default().fg(app.theme.border));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .direction(Direction::Vertical)
+        .split(inner);
+    InputView::new(&app.preset_name_input).draw(f, chunks[0]);
+
+    let mut line = Checkbox::from(app.save_preset_to_cloudwatch).render();
+    line.0.extend(vec![
+        Span::raw(" also save as a CloudWatch Logs Insights query definition "),
+        Span::styled(
+            format!(
+                "  {} {} groups",
+                app.search_mode,
+                app.group_names.selected.len()
+            ),
+            Style::default().fg(app.theme.muted),
+        ),
+    ]);
+    f.render_widget(Paragraph::new(line), chunks[1]);
 }
 
 fn draw_query_form<B, D: Dispatcher<Message = Message>>(
@@ -36,13 +344,18 @@ fn draw_query_form<B, D: Dispatcher<Message = Message>>(
     B: Backend,
 {
     let border_color = if app.default_query_input.is_focused() || app.duration_input.is_focused() {
-        Color::White
+        app.theme.border
     } else {
-        Color::DarkGray
+        app.theme.inactive_border
     };
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Log filter")
+        .title(match (&app.duration, app.search_mode) {
+            (Duration::Live, _) => "Filter pattern (live tail)",
+            (_, SearchMode::Insights) => "Log filter (Insights)",
+            (_, SearchMode::Query) => "Query (Logs Insights)",
+            (_, SearchMode::Filter) => "Filter pattern (FilterLogEvents)",
+        })
         .border_style(Style::default().fg(border_color));
 
     f.render_widget(block, area);
@@ -80,7 +393,14 @@ where
                 .direction(Direction::Horizontal)
                 .split(chunks[0]);
 
-            draw_groups(f, app, chunks[0]);
+            {
+                let left = Layout::default()
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+                    .direction(Direction::Vertical)
+                    .split(chunks[0]);
+                draw_groups(f, app, left[0]);
+                draw_streams(f, app, left[1]);
+            }
             draw_logs(f, app, chunks[1]);
         }
     }
@@ -90,21 +410,134 @@ fn draw_status<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut
 where
     B: Backend,
 {
-    let text = if app.loading {
-        vec![Spans::from("loading...")]
-    } else {
+    let text = if let Some(n) = app.notifications.current() {
+        vec![Spans::from(Span::styled(
+            format!(
+                "{}: {}  ({}: history)",
+                level_label(n.level),
+                n.message,
+                app.keymap.hint(Context::Global, Action::Notifications)
+            ),
+            Style::default()
+                .fg(level_color(n.level))
+                .add_modifier(Modifier::BOLD),
+        ))]
+    } else if app.logs.is_tailing() {
         vec![Spans::from(format!(
-            "{} items found.",
-            app.logs.items.len()
+            "live tail: {} items{}",
+            app.logs.items.len(),
+            if app.logs.is_following() {
+                ""
+            } else {
+                " (paused, select the last item to resume)"
+            }
         ))]
+    } else if app.loading {
+        match app.query_progress {
+            Some(ref progress) => vec![Spans::from(format!(
+                "running: {} items so far, {}",
+                app.logs.items.len(),
+                progress.summary()
+            ))],
+            None => vec![Spans::from("loading...")],
+        }
+    } else {
+        let found = if app.stats.is_active() {
+            format!(
+                "{} rows. {}: sort by column",
+                app.stats.rows.len(),
+                app.keymap.hint(Context::Logs, Action::Sort)
+            )
+        } else {
+            format!("{} items found.", app.logs.items.len())
+        };
+        let keys = format!(
+            "  ({}: keys)",
+            app.keymap.hint(Context::Global, Action::Help)
+        );
+        match app.query_progress {
+            Some(ref progress) if progress.is_finished() => {
+                vec![Spans::from(format!(
+                    "{} {}.{}",
+                    found,
+                    progress.summary(),
+                    keys
+                ))]
+            }
+            _ => vec![Spans::from(found + &keys)],
+        }
     };
     let block = Block::default()
-        .style(Style::default().bg(Color::Rgb(72, 68, 96)))
+        .style(Style::default().bg(app.theme.highlight))
         .borders(Borders::NONE);
     let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
     f.render_widget(paragraph, area);
 }
 
+fn level_label(level: Level) -> &'static str {
+    match level {
+        Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Info => "info",
+    }
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Error => Color::Red,
+        Level::Warning => Color::Yellow,
+        Level::Info => Color::Cyan,
+    }
+}
+
+fn draw_notifications<B, D: Dispatcher<Message = Message>>(
+    f: &mut Frame<B>,
+    app: &mut App<D>,
+    area: Rect,
+) where
+    B: Backend,
+{
+    if !app.show_notifications {
+        return;
+    }
+    let items: Vec<ListItem> = app
+        .notifications
+        .items
+        .iter()
+        .rev()
+        .map(|n| {
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!("{} ", n.time.format("%H:%M:%S")),
+                    Style::default().fg(app.theme.muted),
+                ),
+                Span::styled(
+                    format!("{:<8}", level_label(n.level)),
+                    Style::default().fg(level_color(n.level)),
+                ),
+                Span::raw(n.message.as_str()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Notifications ({}: close)",
+                    app.keymap.hint(Context::List, Action::Close)
+                ))
+                .border_style(Style::default().fg(app.theme.border)),
+        )
+        .highlight_style(Style::default().bg(app.theme.highlight));
+    let area = area.inner(&Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut app.notifications.state);
+}
+
 fn draw_groups<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
 where
     B: Backend,
@@ -127,9 +560,9 @@ where
 
     let border_color =
         if app.focus_state == FocusTarget::Groups || app.focus_state == FocusTarget::GroupFilter {
-            Color::White
+            app.theme.border
         } else {
-            Color::DarkGray
+            app.theme.inactive_border
         };
 
     f.render_widget(
@@ -154,7 +587,7 @@ where
         .highlight_style(if app.focus_state == FocusTarget::Groups {
             Style::default()
                 .add_modifier(Modifier::BOLD)
-                .bg(Color::Rgb(72, 68, 96))
+                .bg(app.theme.highlight)
         } else {
             Style::default()
         })
@@ -167,6 +600,70 @@ where
     f.render_stateful_widget(groups, inner_chunks[1], &mut app.group_names.state);
 }
 
+fn draw_streams<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
+where
+    B: Backend,
+{
+    let focused = app.focus_state == FocusTarget::Streams;
+    let selected = app
+        .streams
+        .group
+        .as_ref()
+        .and_then(|group| app.streams.selected.get(group))
+        .map(|streams| streams.len())
+        .unwrap_or_default();
+    let title = if app.streams.loading {
+        "Streams (loading...)".to_owned()
+    } else if selected > 0 {
+        format!("Streams ({} selected)", selected)
+    } else {
+        "Streams".to_owned()
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(if focused {
+            app.theme.border
+        } else {
+            app.theme.inactive_border
+        }));
+
+    // Last ingestion time as `MM-DD HH:MM:SS`, like the timestamps of the logs.
+    let streams: Vec<ListItem> = app
+        .streams
+        .items
+        .iter()
+        .map(|stream| {
+            let mut line = Checkbox::from(app.streams.is_selected(&stream.name)).render();
+            let ingested = stream
+                .last_ingestion_time
+                .map(crate::client::format_timestamp)
+                .and_then(|t| t.get(5..19).map(str::to_owned))
+                .unwrap_or_default();
+            line.0.extend(vec![
+                Span::raw(" "),
+                Span::styled(ingested, Style::default().fg(app.theme.muted)),
+                Span::raw(" "),
+                Span::raw(stream.name.clone()),
+            ]);
+            ListItem::new(line)
+        })
+        .collect();
+
+    let streams = List::new(streams)
+        .block(block)
+        .highlight_style(if focused {
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(app.theme.highlight)
+        } else {
+            Style::default()
+        })
+        .highlight_symbol(if focused { "▸" } else { " " });
+
+    f.render_stateful_widget(streams, area, &mut app.streams.state);
+}
+
 fn draw_logs<B, D: Dispatcher<Message = Message>>(f: &mut Frame<B>, app: &mut App<D>, area: Rect)
 where
     B: Backend,
@@ -182,9 +679,9 @@ where
     input.draw(f, inner_chunks[1]);
 
     let border_color = if app.focus_state == FocusTarget::Logs {
-        Color::White
+        app.theme.border
     } else {
-        Color::DarkGray
+        app.theme.inactive_border
     };
 
     let log_block = Block::default()
@@ -192,8 +689,15 @@ where
         .title("Logs")
         .border_style(Style::default().fg(border_color));
 
-    if app.loading {
-        let text = vec![Spans::from("loading...")];
+    // Partial results of a running query are shown as they arrive.
+    if app.loading && app.logs.items.is_empty() && !app.stats.is_active() {
+        let mut text = vec![Spans::from("loading...")];
+        if let Some(ref progress) = app.query_progress {
+            text.push(Spans::from(Span::styled(
+                progress.summary(),
+                Style::default().fg(app.theme.muted),
+            )));
+        }
         let paragraph = Paragraph::new(text)
             .block(log_block)
             .wrap(Wrap { trim: true });
@@ -201,6 +705,22 @@ where
         return;
     }
 
+    let highlight_style = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .bg(app.theme.highlight);
+    let header_style = Style::default()
+        .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        .fg(app.theme.header);
+
+    if app.stats.is_active() {
+        let stats = StatsView::new(&mut app.stats)
+            .block(log_block.title("Stats"))
+            .highlight_style(highlight_style)
+            .header_style(header_style);
+        stats.draw(f, inner_chunks[0]);
+        return;
+    }
+
     if app.logs.items.is_empty() {
         let text = vec![Spans::from("No items")];
         let paragraph = Paragraph::new(text)
@@ -210,13 +730,42 @@ where
         return;
     }
 
+    let list_area = if app.histogram.is_visible() {
+        let chunks = Layout::default()
+            .constraints([Constraint::Length(7), Constraint::Min(1)].as_ref())
+            .direction(Direction::Vertical)
+            .split(inner_chunks[0]);
+        let histogram = Histogram::new(&mut app.histogram)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Timeline ({}/{}: select, {}: zoom in)",
+                        app.keymap.hint(Context::Logs, Action::PrevBucket),
+                        app.keymap.hint(Context::Logs, Action::NextBucket),
+                        app.keymap.hint(Context::Logs, Action::Zoom)
+                    ))
+                    .border_style(Style::default().fg(border_color)),
+            )
+            .highlight_style(Style::default().bg(app.theme.highlight))
+            .caption_style(Style::default().fg(app.theme.muted));
+        histogram.draw(f, chunks[0]);
+        chunks[1]
+    } else {
+        inner_chunks[0]
+    };
+
+    if app.result_table.is_tabular() {
+        let table = ResultTable::new(&mut app.result_table, &app.logs.items)
+            .block(log_block)
+            .highlight_style(highlight_style)
+            .header_style(header_style);
+        table.draw(f, list_area);
+        return;
+    }
+
     let logs = LogList::new(&app.logs.items)
         .block(log_block)
-        .highlight_style(
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Color::White)
-                .bg(Color::Rgb(72, 68, 96)),
-        );
-    f.render_stateful_widget(logs, inner_chunks[0], &mut app.logs.state);
+        .highlight_style(highlight_style);
+    f.render_stateful_widget(logs, list_area, &mut app.logs.state);
 }
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use cloudwatchlogs::error::{DescribeQueryDefinitionsError, PutQueryDefinitionError};
+
+use super::*;
+
+/// DescribeQueryDefinitions returns at most 1000 definitions per page.
+const DEFINITIONS_PAGE_SIZE: i32 = 1000;
+
+impl Client {
+    async fn describe_query_definitions(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Vec<QueryDefinition>> {
+        let mut items = vec![];
+        let mut next_token: Option<String> = None;
+        loop {
+            let res = self
+                .retry_policy
+                .retry(
+                    || {
+                        let mut req = self
+                            .client
+                            .describe_query_definitions()
+                            .max_results(DEFINITIONS_PAGE_SIZE)
+                            .set_next_token(next_token.clone());
+                        if let Some(prefix) = prefix {
+                            req = req.query_definition_name_prefix(prefix);
+                        }
+                        req.send()
+                    },
+                    |e| is_retryable(e, DescribeQueryDefinitionsError::code),
+                )
+                .await
+                .context("failed to describe query definitions.")?;
+
+            items.extend(
+                res.query_definitions
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|d| QueryDefinition {
+                        id: d.query_definition_id,
+                        name: d.name.unwrap_or_default(),
+                        query: d.query_string.unwrap_or_default(),
+                        groups: d.log_group_names.unwrap_or_default(),
+                    }),
+            );
+
+            if res.next_token.is_none() {
+                return Ok(items);
+            }
+            next_token = res.next_token;
+        }
+    }
+}
+
+#[async_trait]
+impl QueryDefinitionsClient for Client {
+    async fn get_query_definitions(&self) -> Result<Vec<QueryDefinition>> {
+        log::debug!("get query definitions");
+        self.describe_query_definitions(None).await
+    }
+
+    async fn put_query_definition(&self, input: QueryDefinition) -> Result<String> {
+        log::debug!("put query definition {}", input.name);
+        // PutQueryDefinition creates another definition with the same name unless given its id.
+        let id = match input.id.clone() {
+            Some(id) => Some(id),
+            None => self
+                .describe_query_definitions(Some(&input.name))
+                .await?
+                .into_iter()
+                .find(|d| d.name == input.name)
+                .and_then(|d| d.id),
+        };
+        let res = self
+            .retry_policy
+            .retry(
+                || {
+                    self.client
+                        .put_query_definition()
+                        .name(&input.name)
+                        .set_query_definition_id(id.clone())
+                        .set_log_group_names(Some(input.groups.clone()))
+                        .query_string(&input.query)
+                        .send()
+                },
+                |e| is_retryable(e, PutQueryDefinitionError::code),
+            )
+            .await
+            .with_context(|| format!("failed to put query definition {}.", input.name))?;
+        Ok(res.query_definition_id.unwrap_or_default())
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use cloudwatchlogs::error::GetLogEventsError;
+
+use super::*;
+
+/// GetLogEvents can return empty pages before reaching any events, give up after this many.
+const MAX_EMPTY_PAGES: usize = 10;
+
+impl Client {
+    /// Up to `limit` events starting at `timestamp` when `forward`, else ending before it.
+    async fn get_log_events_page(
+        &self,
+        input: &LogEventsInput,
+        limit: i32,
+        forward: bool,
+    ) -> Result<Vec<LogEvent>> {
+        let mut next_token: Option<String> = None;
+        for _ in 0..MAX_EMPTY_PAGES {
+            let res = self
+                .retry_policy
+                .retry(
+                    || {
+                        self.client
+                            .get_log_events()
+                            .log_group_name(&input.group)
+                            .log_stream_name(&input.stream)
+                            .set_start_time(Some(input.timestamp).filter(|_| forward))
+                            .set_end_time(Some(input.timestamp).filter(|_| !forward))
+                            .start_from_head(forward)
+                            .limit(limit)
+                            .set_next_token(next_token.clone())
+                            .send()
+                    },
+                    |e| is_retryable(e, GetLogEventsError::code),
+                )
+                .await
+                .with_context(|| format!("failed to get log events of {}.", input.stream))?;
+
+            let events: Vec<LogEvent> = res
+                .events
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| LogEvent {
+                    timestamp: e.timestamp.unwrap_or_default(),
+                    message: e.message.unwrap_or_default(),
+                })
+                .collect();
+            if !events.is_empty() {
+                return Ok(events);
+            }
+
+            // The same token is returned again at either end of the stream.
+            let token = if forward {
+                res.next_forward_token
+            } else {
+                res.next_backward_token
+            };
+            if token.is_none() || token == next_token {
+                break;
+            }
+            next_token = token;
+        }
+        Ok(vec![])
+    }
+}
+
+#[async_trait]
+impl LogEventsClient for Client {
+    async fn get_log_events(&self, input: LogEventsInput) -> Result<LogEventsOutput> {
+        log::trace!("get log events around {}", input.timestamp);
+        let before = self.get_log_events_page(&input, input.limit, false).await?;
+        // One more, as the event itself comes first.
+        let after = self
+            .get_log_events_page(&input, input.limit + 1, true)
+            .await?;
+        Ok(LogEventsOutput { before, after })
+    }
+}
@@ -1,8 +1,15 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use cloudwatchlogs::error::{DescribeLogGroupsError, DescribeLogStreamsError};
 
 use super::*;
 
+/// DescribeLogStreams returns at most 50 streams per page.
+const STREAMS_PAGE_SIZE: i32 = 50;
+
+/// Busy groups have thousands of streams, only the most recent ones are listed.
+const MAX_STREAMS: usize = 500;
+
 #[async_trait]
 impl GroupsClient for Client {
     async fn get_group_names(&self) -> Result<GetGroupsOutput> {
@@ -11,19 +18,18 @@ impl GroupsClient for Client {
         let mut next_token: Option<String> = None;
         loop {
             let res = self
-                .client
-                .describe_log_groups()
-                .set_next_token(next_token.clone())
-                .send()
-                .await;
-            if let Err(cloudwatchlogs::SdkError::ServiceError { ref err, .. }) = res {
-                if let Some("ThrottlingException") = err.code() {
-                    std::thread::sleep(std::time::Duration::from_millis(20));
-                    continue;
-                }
-            }
-
-            let res = res.unwrap();
+                .retry_policy
+                .retry(
+                    || {
+                        self.client
+                            .describe_log_groups()
+                            .set_next_token(next_token.clone())
+                            .send()
+                    },
+                    |e| is_retryable(e, DescribeLogGroupsError::code),
+                )
+                .await
+                .context("failed to describe log groups.")?;
 
             items.extend(
                 res.log_groups
@@ -43,51 +49,49 @@ impl GroupsClient for Client {
     }
 
     async fn get_streams(&self, group_name: &str, since: usize) -> Result<GetStreamsOutput> {
-        let mut items: Vec<String> = vec![];
+        let mut items: Vec<LogStream> = vec![];
         let mut next_token = None;
         loop {
             let res = self
-                .client
-                .describe_log_streams()
-                .log_group_name(group_name)
-                .order_by(cloudwatchlogs::model::OrderBy::LastEventTime)
-                .descending(true)
-                .set_next_token(next_token.clone())
-                .limit(1)
-                .send()
-                .await;
+                .retry_policy
+                .retry(
+                    || {
+                        self.client
+                            .describe_log_streams()
+                            .log_group_name(group_name)
+                            .order_by(cloudwatchlogs::model::OrderBy::LastEventTime)
+                            .descending(true)
+                            .set_next_token(next_token.clone())
+                            .limit(STREAMS_PAGE_SIZE)
+                            .send()
+                    },
+                    |e| is_retryable(e, DescribeLogStreamsError::code),
+                )
+                .await
+                .with_context(|| format!("failed to describe log streams of {}.", group_name))?;
 
             log::debug!("describe log streams response is {:?}", &res);
 
-            if let Err(cloudwatchlogs::SdkError::ServiceError { ref err, .. }) = res {
-                if let Some("ThrottlingException") = err.code() {
-                    std::thread::sleep(std::time::Duration::from_millis(20));
-                    continue;
-                }
-            }
-
-            let res = res.unwrap();
-
             next_token = res.next_token;
 
             log::debug!("nextToken is {:?}", &next_token);
 
-            if let Some(stream) = res.log_streams {
-                for s in stream {
-                    if s.last_ingestion_time
-                        .expect("should last ingestion_time exists.")
-                        < since as i64
-                    {
-                        return Ok(GetStreamsOutput { items });
-                    }
-                    items.push(s.log_stream_name.expect("should stream name exists."))
-                }
-                if next_token.is_none() {
+            for s in res.log_streams.unwrap_or_default() {
+                // Streams are ordered by last event time, so the rest are older.
+                if s.last_ingestion_time.unwrap_or_default() < since as i64
+                    || items.len() >= MAX_STREAMS
+                {
                     return Ok(GetStreamsOutput { items });
                 }
-            } else {
+                items.push(LogStream {
+                    name: s.log_stream_name.unwrap_or_default(),
+                    last_event_time: s.last_event_timestamp,
+                    last_ingestion_time: s.last_ingestion_time,
+                })
+            }
+            if next_token.is_none() {
                 return Ok(GetStreamsOutput { items });
-            };
+            }
         }
     }
 }
@@ -0,0 +1,538 @@
+mod pattern;
+mod query;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use self::{pattern::TermPattern, query::LocalQuery};
+use super::*;
+
+/// A single log event in a fixture file.
+///
+/// Fixtures are JSON Lines files, one event per line:
+///
+/// ```json
+/// {"group": "/aws/lambda/app", "stream": "2021/09/01/[$LATEST]abc", "timestamp": 1630454400000, "message": "hello"}
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct LocalEvent {
+    pub group: String,
+    #[serde(default)]
+    pub stream: String,
+    /// Milliseconds since the epoch, like CloudWatch Logs.
+    pub timestamp: i64,
+    pub message: String,
+    /// Position in the loaded fixtures, used as `@ptr` and event id.
+    #[serde(skip)]
+    pub id: usize,
+}
+
+/// Logs Insights prefixes `@log` with the account id.
+const LOCAL_ACCOUNT_ID: &str = "000000000000";
+
+/// FilterLogEvents returns at most this many events per page.
+const FILTER_PAGE_SIZE: usize = 10_000;
+
+impl LocalEvent {
+    fn to_result_item(&self) -> SearchResultItem {
+        let fields = vec![
+            ("@timestamp", format_timestamp(self.timestamp)),
+            ("@message", self.message.clone()),
+            ("@log", format!("{}:{}", LOCAL_ACCOUNT_ID, self.group)),
+            ("@logStream", self.stream.clone()),
+            ("@ptr", self.id.to_string()),
+        ];
+        SearchResultItem {
+            fields: fields
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+        }
+    }
+}
+
+/// Results of a started query, handed out by `get_query_results`.
+type LocalQueryResults = (Vec<SearchResultItem>, QueryStatistics);
+
+/// Serves log groups and events from a directory of `*.jsonl` fixtures,
+/// so the TUI can be run without AWS credentials.
+#[derive(Debug, Clone)]
+pub struct LocalClient {
+    events: Arc<Vec<LocalEvent>>,
+    queries: Arc<Mutex<HashMap<String, LocalQueryResults>>>,
+    next_query_id: Arc<AtomicUsize>,
+    /// Query definitions put during this run, they aren't written back to the fixtures.
+    definitions: Arc<Mutex<Vec<QueryDefinition>>>,
+}
+
+impl LocalClient {
+    pub fn new(mut events: Vec<LocalEvent>) -> Self {
+        for (id, e) in events.iter_mut().enumerate() {
+            e.id = id;
+        }
+        Self {
+            events: Arc::new(events),
+            queries: Arc::new(Mutex::new(HashMap::new())),
+            next_query_id: Arc::new(AtomicUsize::new(0)),
+            definitions: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .with_context(|| format!("failed to read fixture dir {}.", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension() == Some(OsStr::new("jsonl")))
+            .collect();
+        paths.sort();
+
+        let mut events = vec![];
+        for path in paths {
+            let file = File::open(&path)
+                .with_context(|| format!("failed to open fixture {}.", path.display()))?;
+            for (i, line) in BufReader::new(file).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let event: LocalEvent = serde_json::from_str(&line)
+                    .with_context(|| format!("invalid event at {}:{}.", path.display(), i + 1))?;
+                events.push(event);
+            }
+        }
+        log::debug!(
+            "loaded {} local events from {}",
+            events.len(),
+            dir.display()
+        );
+        Ok(Self::new(events))
+    }
+}
+
+#[async_trait]
+impl GroupsClient for LocalClient {
+    async fn get_group_names(&self) -> Result<GetGroupsOutput> {
+        let mut items: Vec<String> = self.events.iter().map(|e| e.group.clone()).collect();
+        items.sort();
+        items.dedup();
+        Ok(GetGroupsOutput { items })
+    }
+
+    async fn get_streams(&self, group_name: &str, since: usize) -> Result<GetStreamsOutput> {
+        let mut last_event_times: BTreeMap<&str, i64> = BTreeMap::new();
+        for e in self.events.iter().filter(|e| e.group == group_name) {
+            let t = last_event_times
+                .entry(e.stream.as_str())
+                .or_insert(e.timestamp);
+            *t = (*t).max(e.timestamp);
+        }
+        let mut streams: Vec<(&str, i64)> = last_event_times
+            .into_iter()
+            .filter(|(_, t)| *t >= since as i64)
+            .collect();
+        streams.sort_by_key(|(_, t)| std::cmp::Reverse(*t));
+        Ok(GetStreamsOutput {
+            items: streams
+                .into_iter()
+                .map(|(name, t)| LogStream {
+                    name: name.to_owned(),
+                    last_event_time: Some(t),
+                    last_ingestion_time: Some(t),
+                })
+                .collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl QueryClient for LocalClient {
+    async fn start_query(&self, input: StartQueryInput) -> Result<QueryId> {
+        let query = LocalQuery::parse(&input.query)?;
+        let (start, end) = (input.start * 1000, input.end * 1000);
+        let events: Vec<&LocalEvent> = self
+            .events
+            .iter()
+            .filter(|e| input.groups.contains(&e.group))
+            .filter(|e| e.timestamp >= start && e.timestamp <= end)
+            .collect();
+
+        let mut items = query.run(events.iter().copied());
+        let statistics = QueryStatistics {
+            records_matched: items.len() as f64,
+            records_scanned: events.len() as f64,
+            bytes_scanned: events.iter().map(|e| e.message.len()).sum::<usize>() as f64,
+            ..Default::default()
+        };
+        items.truncate(DEFAULT_LIMIT as usize);
+
+        let id = format!(
+            "local-{}",
+            self.next_query_id.fetch_add(1, Ordering::SeqCst)
+        );
+        self.queries
+            .lock()
+            .map_err(|_| anyhow!("local query store is poisoned."))?
+            .insert(id.clone(), (items, statistics));
+        Ok(QueryId::new(id))
+    }
+
+    async fn get_query_results(&self, query_id: &QueryId) -> Result<SearchResult> {
+        let id: String = query_id.into();
+        let (items, statistics) = self
+            .queries
+            .lock()
+            .map_err(|_| anyhow!("local query store is poisoned."))?
+            .remove(&id)
+            .ok_or_else(|| anyhow!("unknown query id {}.", id))?;
+        Ok(SearchResult::Complete(items, statistics))
+    }
+
+    async fn stop_query(&self, id: &QueryId) -> Result<()> {
+        let id: String = id.into();
+        if let Ok(mut queries) = self.queries.lock() {
+            queries.remove(&id);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FilterLogClient for LocalClient {
+    async fn filter_logs(&self, input: FilterLogsInput) -> Result<FilterOutput> {
+        let pattern = TermPattern::parse(&input.filter_pattern)?;
+        let prefix = input.stream_name_prefix.as_deref().unwrap_or_default();
+        let (start, end) = (input.start * 1000, input.end * 1000);
+        let group = match input.groups.get(input.cursor.group) {
+            Some(group) => group,
+            None => {
+                return Ok(FilterOutput {
+                    items: vec![],
+                    next: None,
+                })
+            }
+        };
+        // Pages are numbered by their offset, like an opaque token.
+        let offset: usize = match input.cursor.next_token {
+            Some(ref token) => token
+                .parse()
+                .with_context(|| format!("invalid next token {}.", token))?,
+            None => 0,
+        };
+        let mut items: Vec<FilterOutputItem> = self
+            .events
+            .iter()
+            .filter(|e| &e.group == group)
+            .filter(|e| e.stream.starts_with(prefix))
+            .filter(|e| match input.stream_names.get(&e.group) {
+                Some(streams) => streams.contains(&e.stream),
+                None => true,
+            })
+            .filter(|e| e.timestamp >= start && e.timestamp <= end)
+            .filter(|e| pattern.is_match(&e.message))
+            .map(|e| FilterOutputItem {
+                event_id: e.id.to_string(),
+                group: e.group.clone(),
+                stream: e.stream.clone(),
+                message: e.message.clone(),
+                timestamp: e.timestamp,
+            })
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.timestamp));
+        let more = items.len() > offset + FILTER_PAGE_SIZE;
+        let items: Vec<FilterOutputItem> = items
+            .into_iter()
+            .skip(offset)
+            .take(FILTER_PAGE_SIZE)
+            .collect();
+        let next = if more {
+            Some(FilterLogsCursor {
+                group: input.cursor.group,
+                next_token: Some((offset + FILTER_PAGE_SIZE).to_string()),
+            })
+        } else if input.cursor.group + 1 < input.groups.len() {
+            Some(FilterLogsCursor {
+                group: input.cursor.group + 1,
+                next_token: None,
+            })
+        } else {
+            None
+        };
+        Ok(FilterOutput { items, next })
+    }
+}
+
+#[async_trait]
+impl LogEventsClient for LocalClient {
+    async fn get_log_events(&self, input: LogEventsInput) -> Result<LogEventsOutput> {
+        let mut events: Vec<&LocalEvent> = self
+            .events
+            .iter()
+            .filter(|e| e.group == input.group && e.stream == input.stream)
+            .collect();
+        events.sort_by_key(|e| (e.timestamp, e.id));
+        let at = events.partition_point(|e| e.timestamp < input.timestamp);
+        let limit = input.limit.max(0) as usize;
+        let to_event = |e: &&LocalEvent| LogEvent {
+            timestamp: e.timestamp,
+            message: e.message.clone(),
+        };
+        Ok(LogEventsOutput {
+            before: events[at.saturating_sub(limit)..at]
+                .iter()
+                .map(to_event)
+                .collect(),
+            after: events[at..].iter().take(limit + 1).map(to_event).collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl QueryDefinitionsClient for LocalClient {
+    async fn get_query_definitions(&self) -> Result<Vec<QueryDefinition>> {
+        Ok(self
+            .definitions
+            .lock()
+            .map_err(|_| anyhow!("local query definitions are poisoned."))?
+            .clone())
+    }
+
+    async fn put_query_definition(&self, mut input: QueryDefinition) -> Result<String> {
+        let mut definitions = self
+            .definitions
+            .lock()
+            .map_err(|_| anyhow!("local query definitions are poisoned."))?;
+        let index = definitions
+            .iter()
+            .position(|d| (input.id.is_some() && d.id == input.id) || d.name == input.name);
+        let id = match index {
+            Some(i) => definitions[i].id.clone().unwrap_or_default(),
+            None => format!("local-definition-{}", definitions.len()),
+        };
+        input.id = Some(id.clone());
+        match index {
+            Some(i) => definitions[i] = input,
+            None => definitions.push(input),
+        }
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API: &str = "/aws/lambda/kanten-api";
+    const WORKER: &str = "/aws/lambda/kanten-worker";
+    const WEB: &str = "/ecs/kanten-web";
+    const START: i64 = 1633046400;
+    const END: i64 = 1633046700;
+
+    fn client() -> LocalClient {
+        LocalClient::from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")).unwrap()
+    }
+
+    fn query_input(query: &str, groups: &[&str]) -> StartQueryInput {
+        StartQueryInput {
+            start: START,
+            end: END,
+            query: query.to_owned(),
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+            stream_names: HashMap::new(),
+        }
+    }
+
+    fn filter_input(pattern: &str, groups: &[&str]) -> FilterLogsInput {
+        FilterLogsInput {
+            start: START,
+            end: END,
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+            stream_name_prefix: None,
+            stream_names: HashMap::new(),
+            filter_pattern: pattern.to_owned(),
+            cursor: FilterLogsCursor::default(),
+        }
+    }
+
+    async fn query(client: &LocalClient, input: StartQueryInput) -> Vec<SearchResultItem> {
+        let id = client.start_query(input).await.unwrap();
+        match client.get_query_results(&id).await.unwrap() {
+            SearchResult::Complete(items, _) => items,
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_groups_and_streams() {
+        let client = client();
+        assert_eq!(
+            client.get_group_names().await.unwrap().items,
+            vec![API, WORKER, WEB]
+        );
+        let streams: Vec<String> = client
+            .get_streams(API, 0)
+            .await
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(
+            streams,
+            vec!["2021/10/01/[$LATEST]3d4e5f", "2021/10/01/[$LATEST]0a1b2c"]
+        );
+        assert!(client
+            .get_streams(API, 1633046460311)
+            .await
+            .unwrap()
+            .items
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn queries_the_selected_groups_and_time_range() {
+        let client = client();
+        let items = query(
+            &client,
+            query_input(default_query("error").as_str(), &[API, WORKER]),
+        )
+        .await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0].get("@log"),
+            Some("000000000000:/aws/lambda/kanten-api")
+        );
+
+        let items = query(
+            &client,
+            query_input("fields @message | sort @timestamp asc", &[WORKER]),
+        )
+        .await;
+        assert_eq!(items[0].message(), "job 1024 started");
+        assert_eq!(items.len(), 3);
+
+        let mut input = query_input("fields @message", &[WEB]);
+        input.end = 1633046420;
+        assert_eq!(query(&client, input).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn results_are_handed_out_once() {
+        let client = client();
+        let id = client
+            .start_query(query_input("fields @message", &[API]))
+            .await
+            .unwrap();
+        let statistics = match client.get_query_results(&id).await.unwrap() {
+            SearchResult::Complete(_, statistics) => statistics,
+            result => panic!("unexpected {:?}", result),
+        };
+        assert_eq!(statistics.records_matched, 7.0);
+        assert_eq!(statistics.records_scanned, 7.0);
+        assert!(client.get_query_results(&id).await.is_err());
+        assert!(client
+            .start_query(query_input("unmask @message", &[API]))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn filters_with_term_patterns_and_streams() {
+        let client = client();
+        let (items, _) = filter_all(
+            &client,
+            filter_input("request -health", &[API, WORKER]),
+            100,
+        )
+        .await
+        .unwrap();
+        let messages: Vec<&str> = items.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![r#"{"level":"info","msg":"request received","path":"/users/42","method":"GET"}"#]
+        );
+
+        let mut input = filter_input("?START ?END", &[API]);
+        input.stream_names.insert(
+            API.to_owned(),
+            vec!["2021/10/01/[$LATEST]3d4e5f".to_owned()],
+        );
+        let output = client.filter_logs(input).await.unwrap();
+        let timestamps: Vec<i64> = output.items.iter().map(|i| i.timestamp).collect();
+        assert_eq!(timestamps, vec![1633046460310, 1633046460000]);
+        assert_eq!(output.next, None);
+    }
+
+    #[tokio::test]
+    async fn filters_a_group_per_page() {
+        let client = client();
+        let output = client
+            .filter_logs(filter_input("", &[WORKER, WEB]))
+            .await
+            .unwrap();
+        assert_eq!(output.items.len(), 3);
+        assert_eq!(
+            output.next,
+            Some(FilterLogsCursor {
+                group: 1,
+                next_token: None
+            })
+        );
+
+        let (items, truncated) = filter_all(&client, filter_input("", &[WORKER, WEB]), 100)
+            .await
+            .unwrap();
+        let timestamps: Vec<i64> = items.iter().map(|i| i.timestamp).collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                1633046470000,
+                1633046441000,
+                1633046435000,
+                1633046430000,
+                1633046410000
+            ]
+        );
+        assert!(!truncated);
+
+        let (items, truncated) = filter_all(&client, filter_input("", &[WORKER, WEB]), 4)
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 4);
+        assert!(truncated);
+        let (_, truncated) = filter_all(&client, filter_input("", &[WORKER, WEB]), 5)
+            .await
+            .unwrap();
+        assert!(!truncated);
+    }
+
+    #[tokio::test]
+    async fn gets_the_events_around_one() {
+        let output = client()
+            .get_log_events(LogEventsInput {
+                group: API.to_owned(),
+                stream: "2021/10/01/[$LATEST]0a1b2c".to_owned(),
+                timestamp: 1633046400450,
+                limit: 1,
+            })
+            .await
+            .unwrap();
+        let before: Vec<i64> = output.before.iter().map(|e| e.timestamp).collect();
+        let after: Vec<i64> = output.after.iter().map(|e| e.timestamp).collect();
+        assert_eq!(before, vec![1633046400120]);
+        assert_eq!(after, vec![1633046400450, 1633046400460]);
+    }
+}
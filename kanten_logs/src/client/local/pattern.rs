@@ -0,0 +1,95 @@
+use anyhow::{bail, Result};
+
+/// The term subset of the CloudWatch Logs filter pattern syntax:
+/// `ERROR "connection reset" -healthcheck ?WARN ?FATAL`.
+///
+/// JSON (`{ $.level = ... }`) and space-delimited (`[ip, ...]`) patterns are
+/// only understood by CloudWatch Logs itself.
+#[derive(Debug, Default)]
+pub struct TermPattern {
+    required: Vec<String>,
+    any: Vec<String>,
+    excluded: Vec<String>,
+}
+
+impl TermPattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        if pattern.starts_with('{') || pattern.starts_with('[') {
+            bail!(
+                "JSON and space-delimited filter patterns are not supported by the local backend."
+            );
+        }
+
+        let mut terms: Vec<String> = vec![];
+        let mut term = String::new();
+        let mut quoted = false;
+        for c in pattern.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                c if c.is_whitespace() && !quoted => {
+                    if !term.is_empty() {
+                        terms.push(std::mem::take(&mut term));
+                    }
+                }
+                c => term.push(c),
+            }
+        }
+        if !term.is_empty() {
+            terms.push(term);
+        }
+
+        let mut p = Self::default();
+        for term in terms {
+            if let Some(t) = term.strip_prefix('?') {
+                p.any.push(t.to_owned());
+            } else if let Some(t) = term.strip_prefix('-') {
+                p.excluded.push(t.to_owned());
+            } else {
+                p.required.push(term);
+            }
+        }
+        Ok(p)
+    }
+
+    pub fn is_match(&self, message: &str) -> bool {
+        self.required.iter().all(|t| message.contains(t.as_str()))
+            && !self.excluded.iter().any(|t| message.contains(t.as_str()))
+            && (self.any.is_empty() || self.any.iter().any(|t| message.contains(t.as_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: &str, message: &str) -> bool {
+        TermPattern::parse(pattern).unwrap().is_match(message)
+    }
+
+    #[test]
+    fn matches_every_required_term() {
+        assert!(is_match("", "anything"));
+        assert!(is_match("ERROR reset", "ERROR: connection reset"));
+        assert!(!is_match("ERROR reset", "ERROR: timed out"));
+        assert!(is_match(r#""connection reset""#, "ERROR: connection reset"));
+        assert!(!is_match(
+            r#""reset connection""#,
+            "ERROR: connection reset"
+        ));
+    }
+
+    #[test]
+    fn optional_and_excluded_terms() {
+        assert!(is_match("?WARN ?ERROR", "WARN: slow"));
+        assert!(!is_match("?WARN ?ERROR", "INFO: ok"));
+        assert!(is_match("GET -healthcheck", "GET /users"));
+        assert!(!is_match("GET -healthcheck", "GET /healthcheck"));
+    }
+
+    #[test]
+    fn rejects_json_and_space_delimited_patterns() {
+        assert!(TermPattern::parse(r#"{ $.level = "error" }"#).is_err());
+        assert!(TermPattern::parse("[ip, user, ...]").is_err());
+    }
+}
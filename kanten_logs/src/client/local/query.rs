@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+use super::{format_timestamp, LocalEvent, SearchResultItem};
+
+/// The subset of the Logs Insights query syntax the local backend understands:
+///
+/// ```text
+/// fields @timestamp, @message | filter @message like /error/ | sort @timestamp asc | limit 20
+/// filter @message like /ERROR/ | stats count(*) by bin(5m)
+/// ```
+///
+/// `fields` and `display` are accepted but every event is returned with all of its fields.
+/// `stats` only supports `count(*)`, grouped by `bin(...)`, `@log` or `@logStream`.
+#[derive(Debug)]
+pub struct LocalQuery {
+    filters: Vec<Filter>,
+    stats: Option<Stats>,
+    ascending: bool,
+    limit: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Stats {
+    name: String,
+    by: Option<GroupBy>,
+}
+
+#[derive(Debug)]
+enum GroupBy {
+    Bin { name: String, millis: i64 },
+    Field(Field),
+}
+
+#[derive(Debug)]
+struct Filter {
+    field: Field,
+    re: Regex,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Message,
+    LogStream,
+    Log,
+}
+
+impl LocalQuery {
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut q = LocalQuery {
+            filters: vec![],
+            stats: None,
+            ascending: false,
+            limit: None,
+        };
+        for command in split_commands(query) {
+            let (name, args) = command
+                .split_once(char::is_whitespace)
+                .unwrap_or((command, ""));
+            let args = args.trim();
+            match name {
+                "fields" | "display" => {}
+                "filter" => q.filters.push(Filter::parse(args)?),
+                "stats" => q.stats = Some(Stats::parse(args)?),
+                "sort" => q.ascending = args.ends_with("asc"),
+                "limit" => {
+                    q.limit = Some(
+                        args.parse()
+                            .with_context(|| format!("invalid limit {}.", args))?,
+                    )
+                }
+                _ => bail!("`{}` is not supported by the local backend.", name),
+            }
+        }
+        Ok(q)
+    }
+
+    pub fn run<'a>(&self, events: impl Iterator<Item = &'a LocalEvent>) -> Vec<SearchResultItem> {
+        let mut events: Vec<&LocalEvent> = events
+            .filter(|e| self.filters.iter().all(|f| f.is_match(e)))
+            .collect();
+        if self.ascending {
+            events.sort_by_key(|e| e.timestamp);
+        } else {
+            events.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        }
+        let mut items = match self.stats {
+            Some(ref stats) => stats.run(&events),
+            None => events.into_iter().map(|e| e.to_result_item()).collect(),
+        };
+        if let Some(limit) = self.limit {
+            items.truncate(limit);
+        }
+        items
+    }
+}
+
+impl Stats {
+    fn parse(args: &str) -> Result<Self> {
+        let re = Regex::new(r"^count\(\*\)(?:\s+as\s+(\w+))?(?:\s+by\s+(.+))?$")?;
+        let caps = match re.captures(args) {
+            Some(caps) => caps,
+            None => bail!(
+                "`stats {}` is not supported by the local backend, use `stats count(*) by ...`.",
+                args
+            ),
+        };
+        let by = match caps.get(2).map(|m| m.as_str().trim()) {
+            None => None,
+            Some(by) if by.starts_with("bin(") && by.ends_with(')') => {
+                let period = humantime::parse_duration(&by[4..by.len() - 1])
+                    .with_context(|| format!("invalid bin period in {}.", by))?;
+                Some(GroupBy::Bin {
+                    name: by.to_owned(),
+                    millis: (period.as_millis() as i64).max(1),
+                })
+            }
+            Some(by) => Some(GroupBy::Field(Field::parse(by)?)),
+        };
+        Ok(Stats {
+            name: caps
+                .get(1)
+                .map(|m| m.as_str())
+                .unwrap_or("count(*)")
+                .to_owned(),
+            by,
+        })
+    }
+
+    fn run(&self, events: &[&LocalEvent]) -> Vec<SearchResultItem> {
+        let (name, counts) = match self.by {
+            None => {
+                return vec![SearchResultItem {
+                    fields: vec![(self.name.clone(), events.len().to_string())],
+                }]
+            }
+            Some(GroupBy::Bin { ref name, millis }) => {
+                let mut counts: BTreeMap<i64, usize> = BTreeMap::new();
+                for e in events {
+                    *counts
+                        .entry(e.timestamp - e.timestamp % millis)
+                        .or_default() += 1;
+                }
+                let counts: Vec<(String, usize)> = counts
+                    .into_iter()
+                    .rev()
+                    .map(|(bin, count)| (format_timestamp(bin), count))
+                    .collect();
+                (name.clone(), counts)
+            }
+            Some(GroupBy::Field(field)) => {
+                let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+                for e in events {
+                    *counts.entry(field.value(e)).or_default() += 1;
+                }
+                let mut counts: Vec<(String, usize)> = counts
+                    .into_iter()
+                    .map(|(value, count)| (value.to_owned(), count))
+                    .collect();
+                counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+                (field.name().to_owned(), counts)
+            }
+        };
+        counts
+            .into_iter()
+            .map(|(value, count)| SearchResultItem {
+                fields: vec![
+                    (name.clone(), value),
+                    (self.name.clone(), count.to_string()),
+                ],
+            })
+            .collect()
+    }
+}
+
+impl Field {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "@message" => Ok(Field::Message),
+            "@logStream" => Ok(Field::LogStream),
+            "@log" => Ok(Field::Log),
+            f => bail!("{} is not supported by the local backend.", f),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Message => "@message",
+            Field::LogStream => "@logStream",
+            Field::Log => "@log",
+        }
+    }
+
+    fn value(self, e: &LocalEvent) -> &str {
+        match self {
+            Field::Message => &e.message,
+            Field::LogStream => &e.stream,
+            Field::Log => &e.group,
+        }
+    }
+}
+
+impl Filter {
+    fn parse(args: &str) -> Result<Self> {
+        let re = Regex::new(r#"^(@\w+)\s+(not\s+)?in\s+(\[.*\])$"#)?;
+        if let Some(caps) = re.captures(args) {
+            let values: Vec<String> = serde_json::from_str(&caps[3])
+                .with_context(|| format!("invalid list {}.", &caps[3]))?;
+            let values: Vec<String> = values.iter().map(|v| regex::escape(v)).collect();
+            return Ok(Filter {
+                field: Field::parse(&caps[1])?,
+                re: Regex::new(&format!("^(?:{})$", values.join("|")))?,
+                negated: caps.get(2).is_some(),
+            });
+        }
+        let re = Regex::new(r#"^(@\w+)\s+(not\s+)?like\s+(?:/(.*)/|"(.*)")$"#)?;
+        let caps = match re.captures(args) {
+            Some(caps) => caps,
+            None => bail!(
+                "`filter {}` is not supported by the local backend, use `filter @message like /.../` or `filter @logStream in [\"...\"]`.",
+                args
+            ),
+        };
+        let field = Field::parse(&caps[1])?;
+        let pattern = match (caps.get(3), caps.get(4)) {
+            (Some(re), _) => re.as_str().to_owned(),
+            (_, Some(s)) => regex::escape(s.as_str()),
+            _ => String::new(),
+        };
+        Ok(Filter {
+            field,
+            re: Regex::new(&pattern).context("invalid regular expression in filter.")?,
+            negated: caps.get(2).is_some(),
+        })
+    }
+
+    fn is_match(&self, e: &LocalEvent) -> bool {
+        self.re.is_match(self.field.value(e)) != self.negated
+    }
+}
+
+/// Split a query on `|`, ignoring pipes inside `/regex/` and quoted strings,
+/// which may contain escaped quotes.
+fn split_commands(query: &str) -> Vec<&str> {
+    let mut commands = vec![];
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in query.char_indices() {
+        match (quote, c) {
+            _ if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (None, '/') if starts_regex(&query[..i]) => quote = Some(c),
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '|') => {
+                commands.push(query[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    commands.push(query[start..].trim());
+    commands.into_iter().filter(|c| !c.is_empty()).collect()
+}
+
+/// Whether a `/` after `before` starts a regex, which it only does after `like`
+/// or `=~`. Elsewhere it divides, as in `stats count(*)/2`.
+fn starts_regex(before: &str) -> bool {
+    let before = before.trim_end();
+    if before.ends_with("=~") {
+        return true;
+    }
+    let word_start = before
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    before[word_start..].eq_ignore_ascii_case("like")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: usize, stream: &str, timestamp: i64, message: &str) -> LocalEvent {
+        LocalEvent {
+            group: "/app".to_owned(),
+            stream: stream.to_owned(),
+            timestamp,
+            message: message.to_owned(),
+            id,
+        }
+    }
+
+    fn events() -> Vec<LocalEvent> {
+        vec![
+            event(0, "a", 1_000, "INFO started"),
+            event(1, "a", 61_000, "ERROR failed | retrying"),
+            event(2, "b", 62_000, "ERROR failed again"),
+            event(3, "b", 121_000, "INFO done"),
+        ]
+    }
+
+    fn run(query: &str) -> Vec<SearchResultItem> {
+        LocalQuery::parse(query).unwrap().run(events().iter())
+    }
+
+    fn messages(items: &[SearchResultItem]) -> Vec<&str> {
+        items.iter().map(|item| item.message()).collect()
+    }
+
+    #[test]
+    fn split_commands_ignores_pipes_in_regexes_and_strings() {
+        assert_eq!(
+            split_commands(
+                r#"fields @message | filter @message like /a|b/ | filter @logStream in ["c|d"]|"#
+            ),
+            vec![
+                "fields @message",
+                "filter @message like /a|b/",
+                r#"filter @logStream in ["c|d"]"#
+            ]
+        );
+        assert_eq!(
+            split_commands(r#"filter @message like "say \"|\"" | limit 1"#),
+            vec![r#"filter @message like "say \"|\"""#, "limit 1"]
+        );
+    }
+
+    #[test]
+    fn split_commands_divides_outside_of_regexes() {
+        assert_eq!(
+            split_commands("fields a/b | stats count(*)/2 by bin(5m) | limit 1"),
+            vec!["fields a/b", "stats count(*)/2 by bin(5m)", "limit 1"]
+        );
+        assert_eq!(
+            split_commands("filter @message LIKE/a|b/ | filter @logStream =~ /c|d/ | limit 1"),
+            vec![
+                "filter @message LIKE/a|b/",
+                "filter @logStream =~ /c|d/",
+                "limit 1"
+            ]
+        );
+        assert_eq!(
+            split_commands("filter unlike / 2 | limit 1"),
+            vec!["filter unlike / 2", "limit 1"]
+        );
+    }
+
+    #[test]
+    fn filters_newest_first() {
+        assert_eq!(
+            messages(&run(
+                "fields @timestamp, @message | filter @message like /ERROR/"
+            )),
+            vec!["ERROR failed again", "ERROR failed | retrying"]
+        );
+    }
+
+    #[test]
+    fn sorts_and_limits() {
+        assert_eq!(
+            messages(&run(
+                "filter @message like /ERROR/ | sort @timestamp asc | limit 1"
+            )),
+            vec!["ERROR failed | retrying"]
+        );
+    }
+
+    #[test]
+    fn negated_and_list_filters() {
+        assert_eq!(
+            messages(&run(
+                r#"filter @message not like "ERROR" | filter @logStream in ["b"]"#
+            )),
+            vec!["INFO done"]
+        );
+        assert_eq!(
+            messages(&run(r#"filter @logStream not in ["b"]"#)),
+            vec!["ERROR failed | retrying", "INFO started"]
+        );
+    }
+
+    #[test]
+    fn results_have_every_field() {
+        let items = run("filter @message like /started/");
+        assert_eq!(
+            items[0].fields,
+            vec![
+                (
+                    "@timestamp".to_owned(),
+                    "1970-01-01 00:00:01.000".to_owned()
+                ),
+                ("@message".to_owned(), "INFO started".to_owned()),
+                ("@log".to_owned(), "000000000000:/app".to_owned()),
+                ("@logStream".to_owned(), "a".to_owned()),
+                ("@ptr".to_owned(), "0".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_by_bin() {
+        let items = run("stats count(*) as n by bin(1m)");
+        let rows: Vec<(&str, &str)> = items
+            .iter()
+            .map(|item| (item.get("bin(1m)").unwrap(), item.get("n").unwrap()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("1970-01-01 00:02:00.000", "1"),
+                ("1970-01-01 00:01:00.000", "2"),
+                ("1970-01-01 00:00:00.000", "1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_by_field_and_in_total() {
+        let items = run("filter @message like /ERROR|done/ | stats count(*) by @logStream");
+        let rows: Vec<(&str, &str)> = items
+            .iter()
+            .map(|item| {
+                (
+                    item.get("@logStream").unwrap(),
+                    item.get("count(*)").unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(rows, vec![("b", "2"), ("a", "1")]);
+        assert_eq!(run("stats count(*)")[0].get("count(*)"), Some("4"));
+    }
+
+    #[test]
+    fn rejects_what_it_does_not_support() {
+        assert!(LocalQuery::parse(r#"parse @message "* *" as a, b"#).is_err());
+        assert!(LocalQuery::parse("stats avg(@duration)").is_err());
+        assert!(LocalQuery::parse("filter @requestId like /x/").is_err());
+        assert!(LocalQuery::parse("filter @message like /(/").is_err());
+        assert!(LocalQuery::parse("limit ten").is_err());
+    }
+}
@@ -1,16 +1,156 @@
+mod definitions;
+mod events;
 mod group;
+mod local;
 mod query;
+mod retry;
+mod sliced;
 mod types;
 
+use anyhow::{Context, Result};
+use chrono::TimeZone;
+
+pub use local::LocalClient;
+pub use sliced::{timestamp_sort, SlicingClient};
 pub use types::*;
 
+use retry::{is_retryable, RetryPolicy};
+
+/// Logs Insights returns at most this many rows per query.
+pub const DEFAULT_LIMIT: i32 = 10_000;
+
+/// LocalStack's edge port, used when `--localstack` is given without an endpoint.
+const LOCALSTACK_ENDPOINT_URL: &str = "http://localhost:4566";
+
+/// A source of log groups and query results the TUI can run against.
+pub trait Backend:
+    GroupsClient
+    + QueryClient
+    + FilterLogClient
+    + LogEventsClient
+    + QueryDefinitionsClient
+    + Clone
+    + Send
+    + Sync
+    + 'static
+{
+}
+
+impl<T> Backend for T where
+    T: GroupsClient
+        + QueryClient
+        + FilterLogClient
+        + LogEventsClient
+        + QueryDefinitionsClient
+        + Clone
+        + Send
+        + Sync
+        + 'static
+{
+}
+
 #[derive(Debug, Clone)]
 pub struct Client {
     client: cloudwatchlogs::Client,
+    retry_policy: RetryPolicy,
 }
 
 impl Client {
     pub fn new(client: cloudwatchlogs::Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Build a client from the shared AWS config, optionally sending requests to
+    /// `endpoint_url` instead of the regional endpoint.
+    ///
+    /// With `localstack`, the endpoint defaults to LocalStack's edge port and dummy
+    /// credentials (and a region, if none is configured) are used so that no real
+    /// account is needed.
+    pub fn from_shared_config(
+        shared_config: &aws_config::Config,
+        endpoint_url: Option<&str>,
+        localstack: bool,
+    ) -> Result<Self> {
+        let mut builder = cloudwatchlogs::config::Builder::from(shared_config);
+        let endpoint_url = endpoint_url.or(if localstack {
+            Some(LOCALSTACK_ENDPOINT_URL)
+        } else {
+            None
+        });
+        if let Some(url) = endpoint_url {
+            log::debug!("use endpoint {}", url);
+            let uri: http::Uri = url
+                .parse()
+                .with_context(|| format!("invalid endpoint url {}.", url))?;
+            builder = builder.endpoint_resolver(cloudwatchlogs::Endpoint::immutable(uri));
+        }
+        if localstack {
+            builder = builder
+                .credentials_provider(cloudwatchlogs::Credentials::from_keys("test", "test", None));
+            if shared_config.region().is_none() {
+                builder = builder.region(cloudwatchlogs::Region::new("us-east-1"));
+            }
+        }
+        Ok(Self::new(cloudwatchlogs::Client::from_conf(
+            builder.build(),
+        )))
     }
 }
+
+/// Fields returned by [`default_query`], plus the `@ptr` Logs Insights adds to every row.
+pub const DEFAULT_FIELDS: [&str; 5] = ["@timestamp", "@message", "@log", "@logStream", "@ptr"];
+
+const DEFAULT_QUERY_PREFIX: &str =
+    "fields @timestamp, @message, @log, @logStream | sort @timestamp desc | filter @message like /";
+
+/// The query run for a plain log filter: matching lines, newest first.
+pub fn default_query(filter: &str) -> String {
+    format!("{}{}/", DEFAULT_QUERY_PREFIX, filter)
+}
+
+/// The filter a query was built from by [`default_query`].
+pub fn default_query_filter(query: &str) -> Option<&str> {
+    query.strip_prefix(DEFAULT_QUERY_PREFIX)?.strip_suffix('/')
+}
+
+/// Fetch the pages of a FilterLogEvents search until it is done or `max` events were
+/// found, newest first. Returns whether the search stopped short.
+pub async fn filter_all<C: FilterLogClient + Sync>(
+    client: &C,
+    mut input: FilterLogsInput,
+    max: usize,
+) -> Result<(Vec<FilterOutputItem>, bool)> {
+    let mut items = vec![];
+    loop {
+        let output = client.filter_logs(input.clone()).await?;
+        items.extend(output.items);
+        match output.next {
+            Some(cursor) if items.len() < max => input.cursor = cursor,
+            next => {
+                let truncated = next.is_some() || items.len() > max;
+                items.truncate(max);
+                items.sort_by_key(|item| std::cmp::Reverse(item.timestamp));
+                return Ok((items, truncated));
+            }
+        }
+    }
+}
+
+/// Format epoch milliseconds the same way Logs Insights formats `@timestamp`.
+pub fn format_timestamp(millis: i64) -> String {
+    chrono::Utc
+        .timestamp_millis_opt(millis)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+        .unwrap_or_default()
+}
+
+/// Parse a timestamp formatted by [`format_timestamp`] back into epoch milliseconds.
+pub fn parse_timestamp(s: &str) -> Option<i64> {
+    chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .map(|t| chrono::Utc.from_utc_datetime(&t).timestamp_millis())
+}
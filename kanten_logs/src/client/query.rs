@@ -1,104 +1,202 @@
 // use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
+use cloudwatchlogs::error::{
+    FilterLogEventsError, GetQueryResultsError, StartQueryError, StopQueryError,
+};
 use cloudwatchlogs::model::{QueryStatus, ResultField};
 
 use super::*;
 
-const DEFAULT_LIMIT: i32 = 10_000;
-
 #[async_trait]
 impl FilterLogClient for Client {
-    async fn filter_logs(&self) -> Result<FilterOutput> {
-        let res = self
-            .client
-            .filter_log_events()
-            .log_group_name("/aws/lambda/test".to_owned())
-            .log_stream_name_prefix("/".to_owned())
-            .start_time(1618773069000)
-            .end_time(1638773069000)
-            .limit(10000)
-            .send()
-            .await?;
-        log::debug!("start query response is {:?}", res);
-        todo!();
+    async fn filter_logs(&self, input: FilterLogsInput) -> Result<FilterOutput> {
+        log::trace!("filter log events");
+        let filter_pattern = if input.filter_pattern.is_empty() {
+            None
+        } else {
+            Some(input.filter_pattern.clone())
+        };
+        // FilterLogEvents accepts a single log group, so page through them one by one.
+        let mut cursor = input.cursor.clone();
+        while let Some(group) = input.groups.get(cursor.group) {
+            // logStreamNames can't be combined with a prefix, so apply the prefix here.
+            let stream_names: Option<Vec<String>> = input.stream_names.get(group).map(|names| {
+                names
+                    .iter()
+                    .filter(|name| {
+                        name.starts_with(input.stream_name_prefix.as_deref().unwrap_or_default())
+                    })
+                    .cloned()
+                    .collect()
+            });
+            let stream_name_prefix = match stream_names {
+                Some(ref names) if names.is_empty() => {
+                    cursor = FilterLogsCursor {
+                        group: cursor.group + 1,
+                        next_token: None,
+                    };
+                    continue;
+                }
+                Some(_) => None,
+                None => input.stream_name_prefix.clone(),
+            };
+            let res = self
+                .retry_policy
+                .retry(
+                    || {
+                        self.client
+                            .filter_log_events()
+                            .log_group_name(group)
+                            .set_log_stream_names(stream_names.clone())
+                            .set_log_stream_name_prefix(stream_name_prefix.clone())
+                            .set_filter_pattern(filter_pattern.clone())
+                            .start_time(input.start * 1000)
+                            .end_time(input.end * 1000)
+                            .set_next_token(cursor.next_token.clone())
+                            .send()
+                    },
+                    |e| is_retryable(e, FilterLogEventsError::code),
+                )
+                .await
+                .context("failed to filter log events.")?;
+
+            let mut items: Vec<FilterOutputItem> = res
+                .events
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| FilterOutputItem {
+                    event_id: e.event_id.unwrap_or_default(),
+                    group: group.clone(),
+                    stream: e.log_stream_name.unwrap_or_default(),
+                    message: e.message.unwrap_or_default(),
+                    timestamp: e.timestamp.unwrap_or_default(),
+                })
+                .collect();
+            items.sort_by_key(|item| std::cmp::Reverse(item.timestamp));
+
+            log::debug!("nextToken is {:?}", &res.next_token);
+            let next = match res.next_token {
+                Some(next_token) => Some(FilterLogsCursor {
+                    group: cursor.group,
+                    next_token: Some(next_token),
+                }),
+                None if cursor.group + 1 < input.groups.len() => Some(FilterLogsCursor {
+                    group: cursor.group + 1,
+                    next_token: None,
+                }),
+                None => None,
+            };
+            return Ok(FilterOutput { items, next });
+        }
+        Ok(FilterOutput {
+            items: vec![],
+            next: None,
+        })
     }
 }
 
 #[async_trait]
 impl QueryClient for Client {
-    async fn start_default_query<'a>(&self, input: StartQueryInput) -> Result<QueryId> {
+    async fn start_query(&self, input: StartQueryInput) -> Result<QueryId> {
         log::trace!("start query");
         // The list of log groups to be queried. You can include up to 20 log groups.
         // See also https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_StartQuery.html
         let res = self
-            .client
-            .start_query()
-            .set_log_group_names(Some(input.groups))
-            .start_time(input.start)
-            .end_time(input.end)
-            .query_string(format!("fields @timestamp, @message, @log | sort @timestamp desc | filter @message like /{}/", input.filter))
-            .limit(DEFAULT_LIMIT)
-            .send()
-            .await?;
+            .retry_policy
+            .retry(
+                || {
+                    self.client
+                        .start_query()
+                        .set_log_group_names(Some(input.groups.clone()))
+                        .start_time(input.start)
+                        .end_time(input.end)
+                        .query_string(input.query.clone())
+                        .limit(DEFAULT_LIMIT)
+                        .send()
+                },
+                |e| is_retryable(e, StartQueryError::code),
+            )
+            .await
+            .context("failed to start query.")?;
         log::trace!("start query response is {:?}", res);
-        Ok(QueryId::new(res.query_id.expect("there is no query id.")))
+        let query_id = res.query_id.context("there is no query id.")?;
+        Ok(QueryId::new(query_id))
     }
 
     async fn stop_query(&self, id: &QueryId) -> Result<()> {
         let res = self
-            .client
-            .stop_query()
-            .set_query_id(Some(id.into()))
-            .send()
-            .await?;
+            .retry_policy
+            .retry(
+                || {
+                    self.client
+                        .stop_query()
+                        .set_query_id(Some(id.into()))
+                        .send()
+                },
+                |e| is_retryable(e, StopQueryError::code),
+            )
+            .await
+            .context("failed to stop query.")?;
         log::trace!("stop query response is {:?}", res);
         Ok(())
     }
 
-    async fn get_default_query_results(&self, query_id: &QueryId) -> Result<SearchResult> {
+    async fn get_query_results(&self, query_id: &QueryId) -> Result<SearchResult> {
         log::trace!("get query results");
         let res = self
-            .client
-            .get_query_results()
-            .query_id(query_id)
-            .send()
+            .retry_policy
+            .retry(
+                || self.client.get_query_results().query_id(query_id).send(),
+                |e| is_retryable(e, GetQueryResultsError::code),
+            )
             .await
             .context("failed to get query result.")?;
 
-        let items = res.results.expect("there is no results.");
+        let items: Vec<SearchResultItem> = res
+            .results
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| SearchResultItem {
+                fields: item
+                    .into_iter()
+                    .map(|ResultField { field, value, .. }| {
+                        (field.unwrap_or_default(), value.unwrap_or_default())
+                    })
+                    .collect(),
+            })
+            .collect();
+        let statistics = res
+            .statistics
+            .map(|s| QueryStatistics {
+                records_matched: s.records_matched,
+                records_scanned: s.records_scanned,
+                bytes_scanned: s.bytes_scanned,
+                ..Default::default()
+            })
+            .unwrap_or_default();
 
         if let Some(status) = res.status {
             log::trace!("response status is {:?}", &status);
-            let items = items
-                .into_iter()
-                .map(|item| {
-                    let mut message = String::default();
-                    let mut timestamp = String::default();
-                    for ResultField { value, field, .. } in item {
-                        let field = field.unwrap();
-                        match field.as_str() {
-                            "@timestamp" => timestamp = value.unwrap(),
-                            "@message" => message = value.unwrap(),
-                            _ => {}
-                        }
-                    }
-                    SearchResultItem { timestamp, message }
-                })
-                .collect();
 
             // Complete
             if status == QueryStatus::Complete {
-                return Ok(SearchResult::Complete(items));
+                return Ok(SearchResult::Complete(items, statistics));
             }
 
             // Running
             if status == QueryStatus::Running && items.len() >= DEFAULT_LIMIT as usize {
-                return Ok(SearchResult::Complete(items));
+                return Ok(SearchResult::Complete(items, statistics));
+            }
+
+            if matches!(
+                status,
+                QueryStatus::Failed | QueryStatus::Cancelled | QueryStatus::Timeout
+            ) {
+                bail!("query finished with status {:?}.", status);
             }
-            // TODO: error handling
         }
-        Ok(SearchResult::Running(query_id.clone()))
+        Ok(SearchResult::Running(query_id.clone(), items, statistics))
     }
 }
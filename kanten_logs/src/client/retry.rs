@@ -0,0 +1,199 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Error codes worth retrying: throttling and transient service failures.
+const RETRYABLE_CODES: [&str; 4] = [
+    "ThrottlingException",
+    "LimitExceededException",
+    "ServiceUnavailableException",
+    "RequestLimitExceeded",
+];
+
+/// Exponential backoff with full jitter, giving up after `max_attempts`.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A random delay up to `base_delay * 2^attempt`, capped at `max_delay`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let cap = self
+            .base_delay
+            .checked_mul(2u32.saturating_pow(attempt))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        let millis = cap.as_millis() as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+    }
+
+    /// Call `f` until it succeeds, fails with an error `is_retryable` rejects, or
+    /// the attempts run out.
+    pub async fn retry<T, E, F, Fut>(
+        &self,
+        mut f: F,
+        is_retryable: impl Fn(&E) -> bool,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Err(e) if attempt + 1 < self.max_attempts && is_retryable(&e) => {
+                    let delay = self.delay(attempt);
+                    log::debug!("retry in {:?} (attempt {})", delay, attempt + 1);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// What went wrong in a call, as far as retrying is concerned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Failure<'a> {
+    /// The service answered with an error, and maybe its code.
+    Service(Option<&'a str>),
+    Timeout,
+    /// The request didn't reach the service, e.g. a connection error.
+    Dispatch,
+    Other,
+}
+
+impl Failure<'_> {
+    fn is_transient(self) -> bool {
+        match self {
+            Failure::Service(code) => code.map(|c| RETRYABLE_CODES.contains(&c)).unwrap_or(false),
+            Failure::Timeout | Failure::Dispatch => true,
+            Failure::Other => false,
+        }
+    }
+}
+
+/// Whether an SDK error is transient, `code` being the operation error's `code` method.
+pub fn is_retryable<E>(err: &cloudwatchlogs::SdkError<E>, code: fn(&E) -> Option<&str>) -> bool {
+    let failure = match err {
+        cloudwatchlogs::SdkError::ServiceError { err, .. } => Failure::Service(code(err)),
+        cloudwatchlogs::SdkError::TimeoutError(_) => Failure::Timeout,
+        cloudwatchlogs::SdkError::DispatchFailure(_) => Failure::Dispatch,
+        _ => Failure::Other,
+    };
+    failure.is_transient()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+        }
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let calls = AtomicU32::new(0);
+        let result = policy()
+            .retry(
+                || {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async { Err::<(), _>("busy") }
+                },
+                |_| true,
+            )
+            .await;
+        assert_eq!(result, Err("busy"));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn stops_on_errors_that_are_not_retryable() {
+        let calls = AtomicU32::new(0);
+        let result = policy()
+            .retry(
+                || {
+                    let call = calls.fetch_add(1, Ordering::SeqCst);
+                    async move { Err::<(), _>(if call == 0 { "busy" } else { "denied" }) }
+                },
+                |e| *e == "busy",
+            )
+            .await;
+        assert_eq!(result, Err("denied"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn returns_the_first_success() {
+        let calls = AtomicU32::new(0);
+        let result = policy()
+            .retry(
+                || {
+                    let call = calls.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        if call < 2 {
+                            Err("busy")
+                        } else {
+                            Ok(call)
+                        }
+                    }
+                },
+                |_| true,
+            )
+            .await;
+        assert_eq!(result, Ok(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn backs_off_exponentially_up_to_max_delay() {
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            assert!(policy.delay(0) <= Duration::from_millis(100));
+            assert!(policy.delay(3) <= Duration::from_millis(800));
+            assert!(policy.delay(10) <= policy.max_delay);
+            // 2^attempt overflows.
+            assert!(policy.delay(40) <= policy.max_delay);
+        }
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(0),
+            ..policy
+        };
+        assert_eq!(policy.delay(5), Duration::from_millis(0));
+    }
+
+    #[test]
+    fn retries_throttling_timeouts_and_dispatch_failures() {
+        assert!(Failure::Service(Some("ThrottlingException")).is_transient());
+        assert!(Failure::Service(Some("LimitExceededException")).is_transient());
+        assert!(Failure::Service(Some("ServiceUnavailableException")).is_transient());
+        assert!(Failure::Timeout.is_transient());
+        assert!(Failure::Dispatch.is_transient());
+
+        assert!(!Failure::Service(Some("AccessDeniedException")).is_transient());
+        assert!(!Failure::Service(Some("ResourceNotFoundException")).is_transient());
+        assert!(!Failure::Service(None).is_transient());
+        assert!(!Failure::Other.is_transient());
+    }
+}
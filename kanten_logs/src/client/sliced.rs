@@ -0,0 +1,731 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use futures::future::join_all;
+
+use super::*;
+
+/// How many sub-queries of a query run at once, well below the account's
+/// concurrent Logs Insights query limit.
+const MAX_CONCURRENT_QUERIES: usize = 4;
+
+/// StartQuery accepts at most this many log groups.
+const MAX_GROUPS_PER_QUERY: usize = 20;
+
+/// Slices are sized to be this full, so that most don't hit the row limit again.
+const SLICE_FILL_RATIO: f64 = 0.8;
+
+/// Split the time range into enough slices for `matched` rows to fit under the
+/// row limit, or nothing when the range can't be split any further.
+fn split(input: &StartQueryInput, matched: f64) -> Vec<StartQueryInput> {
+    let span = input.end - input.start;
+    let parts = (matched / (DEFAULT_LIMIT as f64 * SLICE_FILL_RATIO)).ceil() as i64;
+    let parts = parts.max(2).min(span);
+    if parts < 2 {
+        return vec![];
+    }
+    // Neighbouring slices share their boundary second, duplicates are dropped by `@ptr`.
+    (0..parts)
+        .map(|i| StartQueryInput {
+            start: input.start + span * i / parts,
+            end: input.start + span * (i + 1) / parts,
+            ..input.clone()
+        })
+        .collect()
+}
+
+/// Whether the query aggregates with `stats`, so that its rows have no `@ptr`.
+fn is_stats(query: &str) -> bool {
+    query.split('|').any(|command| {
+        command
+            .split_whitespace()
+            .next()
+            .map(|w| w.eq_ignore_ascii_case("stats"))
+            == Some(true)
+    })
+}
+
+/// Whether the last `sort` of the query orders by `@timestamp`, newest first
+/// (`Some(true)`) or oldest first. Only then can stitched rows be sorted again.
+pub fn timestamp_sort(query: &str) -> Option<bool> {
+    let sort: Vec<&str> = query
+        .split('|')
+        .map(|command| command.split_whitespace().collect::<Vec<&str>>())
+        .rev()
+        .find(|words| words.first().map(|w| w.eq_ignore_ascii_case("sort")) == Some(true))?;
+    match sort.as_slice() {
+        [_, "@timestamp", direction] if direction.eq_ignore_ascii_case("desc") => Some(true),
+        [_, "@timestamp", direction] if direction.eq_ignore_ascii_case("asc") => Some(false),
+        _ => None,
+    }
+}
+
+/// The `limit` the query ends with, which each slice applies on its own.
+fn query_limit(query: &str) -> Option<usize> {
+    let words: Vec<&str> = query.rsplit('|').next()?.split_whitespace().collect();
+    match words.as_slice() {
+        [command, n] if command.eq_ignore_ascii_case("limit") => n.parse().ok(),
+        _ => None,
+    }
+}
+
+/// `filter @logStream in ["a", "b"]`
+fn stream_filter(streams: &[String]) -> String {
+    let streams: Vec<String> = streams
+        .iter()
+        .map(|s| serde_json::to_string(s).unwrap_or_default())
+        .collect();
+    format!("filter @logStream in [{}]", streams.join(", "))
+}
+
+#[derive(Debug)]
+struct Slice {
+    id: QueryId,
+    input: StartQueryInput,
+    /// Rows matched so far.
+    items: Vec<SearchResultItem>,
+    statistics: QueryStatistics,
+}
+
+#[derive(Debug)]
+struct SlicedQuery {
+    /// Newest last, so that the newest rows are fetched first.
+    pending: Vec<StartQueryInput>,
+    running: Vec<Slice>,
+    /// Slices that completed without hitting the row limit.
+    done: Vec<Slice>,
+    /// Rows of slices completed since the last poll.
+    fresh: Vec<SearchResultItem>,
+    statistics: QueryStatistics,
+    /// Whether the results come from more than one query.
+    stitched: bool,
+    /// See [`timestamp_sort`].
+    sort: Option<bool>,
+    limit: Option<usize>,
+}
+
+impl SlicedQuery {
+    fn statistics(&self) -> QueryStatistics {
+        let mut statistics = self.statistics;
+        for slice in self.running.iter() {
+            add_statistics(&mut statistics, &slice.statistics, true);
+        }
+        statistics
+    }
+}
+
+fn add_statistics(total: &mut QueryStatistics, s: &QueryStatistics, matched: bool) {
+    if matched {
+        total.records_matched += s.records_matched;
+    }
+    total.records_scanned += s.records_scanned;
+    total.bytes_scanned += s.bytes_scanned;
+}
+
+/// Runs Logs Insights queries through `C`, fanning out over batches of log
+/// groups and splitting the time range of any query that hits the row limit,
+/// then stitching the slices back together.
+#[derive(Debug, Clone)]
+pub struct SlicingClient<C> {
+    inner: C,
+    queries: Arc<Mutex<HashMap<String, SlicedQuery>>>,
+    next_query_id: Arc<AtomicUsize>,
+}
+
+impl<C> SlicingClient<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            queries: Arc::new(Mutex::new(HashMap::new())),
+            next_query_id: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, SlicedQuery>>> {
+        self.queries
+            .lock()
+            .map_err(|_| anyhow!("sliced query store is poisoned."))
+    }
+}
+
+impl<C: QueryClient + Send + Sync> SlicingClient<C> {
+    async fn start_slices(&self, inputs: Vec<StartQueryInput>) -> Result<Vec<Slice>> {
+        let started = join_all(
+            inputs
+                .iter()
+                .map(|input| self.inner.start_query(input.clone())),
+        )
+        .await;
+        let mut slices = vec![];
+        let mut failure = None;
+        for (input, id) in inputs.into_iter().zip(started) {
+            match id {
+                Ok(id) => slices.push(Slice {
+                    id,
+                    input,
+                    items: vec![],
+                    statistics: QueryStatistics::default(),
+                }),
+                Err(e) => failure = failure.or(Some(e)),
+            }
+        }
+        if let Some(e) = failure {
+            let ids: Vec<QueryId> = slices.into_iter().map(|s| s.id).collect();
+            self.stop_slices(&ids).await;
+            return Err(e);
+        }
+        Ok(slices)
+    }
+
+    /// Stop sub-queries that are no longer needed, so that they free their
+    /// query slots. Failures are only logged, the queries then time out.
+    async fn stop_slices(&self, ids: &[QueryId]) {
+        for result in join_all(ids.iter().map(|id| self.inner.stop_query(id))).await {
+            if let Err(e) = result {
+                log::warn!("{:#}", e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<C: QueryClient + Send + Sync> QueryClient for SlicingClient<C> {
+    async fn start_query(&self, input: StartQueryInput) -> Result<QueryId> {
+        // Groups restricted to some streams get a query of their own with an
+        // `@logStream` filter, the rest fan out over batches of log groups.
+        let (restricted, groups): (Vec<String>, Vec<String>) = input
+            .groups
+            .iter()
+            .cloned()
+            .partition(|group| input.stream_names.contains_key(group));
+        let mut pending: Vec<StartQueryInput> = restricted
+            .into_iter()
+            .map(|group| StartQueryInput {
+                query: format!(
+                    "{} | {}",
+                    stream_filter(&input.stream_names[&group]),
+                    input.query
+                ),
+                groups: vec![group],
+                stream_names: HashMap::new(),
+                ..input.clone()
+            })
+            .collect();
+        pending.extend(
+            groups
+                .chunks(MAX_GROUPS_PER_QUERY)
+                .map(|groups| StartQueryInput {
+                    groups: groups.to_vec(),
+                    stream_names: HashMap::new(),
+                    ..input.clone()
+                }),
+        );
+        if pending.len() > 1 && is_stats(&input.query) {
+            bail!(
+                "stats rows of separate queries can't be merged, so a stats query takes at most {} log groups and streams of only one group (this one needs {} queries).",
+                MAX_GROUPS_PER_QUERY,
+                pending.len()
+            );
+        }
+        let stitched = pending.len() > 1;
+        let rest = pending.split_off(pending.len().min(MAX_CONCURRENT_QUERIES));
+        let running = self.start_slices(pending).await?;
+        let id = format!(
+            "sliced-{}",
+            self.next_query_id.fetch_add(1, Ordering::SeqCst)
+        );
+        self.lock()?.insert(
+            id.clone(),
+            SlicedQuery {
+                pending: rest,
+                running,
+                done: vec![],
+                fresh: vec![],
+                statistics: QueryStatistics::default(),
+                stitched,
+                sort: timestamp_sort(&input.query),
+                limit: query_limit(&input.query),
+            },
+        );
+        Ok(QueryId::new(id))
+    }
+
+    async fn get_query_results(&self, query_id: &QueryId) -> Result<SearchResult> {
+        let id: String = query_id.into();
+        let running: Vec<QueryId> = match self.lock()?.get(&id) {
+            Some(query) => query.running.iter().map(|s| s.id.clone()).collect(),
+            None => return Err(anyhow!("unknown query id {}.", id)),
+        };
+
+        let results = join_all(running.iter().map(|id| self.inner.get_query_results(id))).await;
+
+        let (inputs, to_stop, failure) = {
+            let mut queries = self.lock()?;
+            let query = queries
+                .get_mut(&id)
+                .ok_or_else(|| anyhow!("query {} was stopped.", id))?;
+            // Slices that hit the row limit keep running, and taking a slot, until stopped.
+            let mut to_stop = vec![];
+            let mut failure = None;
+            for (slice_id, result) in running.iter().zip(results) {
+                let index = match query.running.iter().position(|s| &s.id == slice_id) {
+                    Some(index) => index,
+                    None => continue,
+                };
+                match result {
+                    Ok(SearchResult::Running(_, items, statistics)) => {
+                        let slice = &mut query.running[index];
+                        slice.items = items;
+                        slice.statistics = statistics;
+                    }
+                    Ok(SearchResult::Complete(items, statistics)) => {
+                        let slice = query.running.remove(index);
+                        // Aggregated rows can't be stitched together, so only rows of events are split.
+                        let is_aggregate = items.iter().all(|item| item.get("@ptr").is_none());
+                        let slices = if items.len() >= DEFAULT_LIMIT as usize && !is_aggregate {
+                            split(&slice.input, statistics.records_matched)
+                        } else {
+                            vec![]
+                        };
+                        if slices.is_empty() {
+                            add_statistics(&mut query.statistics, &statistics, true);
+                            query.done.push(Slice {
+                                items: items.clone(),
+                                statistics,
+                                ..slice
+                            });
+                        } else {
+                            log::debug!(
+                                "split {} - {} into {} slices",
+                                slice.input.start,
+                                slice.input.end,
+                                slices.len()
+                            );
+                            add_statistics(&mut query.statistics, &statistics, false);
+                            query.pending.extend(slices);
+                            query.stitched = true;
+                            to_stop.push(slice.id);
+                        }
+                        // Even rows of a truncated slice are real matches, so show them meanwhile.
+                        query.fresh.extend(items);
+                    }
+                    Err(e) => {
+                        failure = Some(e);
+                        break;
+                    }
+                }
+            }
+            if failure.is_some() {
+                if let Some(query) = queries.remove(&id) {
+                    to_stop.extend(query.running.into_iter().map(|s| s.id));
+                }
+                (vec![], to_stop, failure)
+            } else {
+                let free = MAX_CONCURRENT_QUERIES.saturating_sub(query.running.len());
+                let at = query.pending.len().saturating_sub(free);
+                (query.pending.drain(at..).rev().collect(), to_stop, None)
+            }
+        };
+
+        self.stop_slices(&to_stop).await;
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        let started = self.start_slices(inputs).await;
+
+        let removed = self.lock()?.remove(&id);
+        let mut query = match removed {
+            Some(query) => query,
+            None => {
+                // Stopped meanwhile.
+                if let Ok(started) = started {
+                    let ids: Vec<QueryId> = started.into_iter().map(|s| s.id).collect();
+                    self.stop_slices(&ids).await;
+                }
+                return Err(anyhow!("query {} was stopped.", id));
+            }
+        };
+        match started {
+            Ok(started) => query.running.extend(started),
+            Err(e) => {
+                let ids: Vec<QueryId> = query.running.into_iter().map(|s| s.id).collect();
+                self.stop_slices(&ids).await;
+                return Err(e);
+            }
+        }
+
+        let mut queries = self.lock()?;
+
+        let mut statistics = query.statistics();
+        if query.stitched {
+            statistics.slices = query.done.len() + query.running.len() + query.pending.len();
+        }
+
+        if query.running.is_empty() && query.pending.is_empty() {
+            let mut done = std::mem::take(&mut query.done);
+            // Batch by batch, in time order, unless the rows can be sorted again.
+            done.sort_by(|a, b| {
+                (&a.input.groups, a.input.start).cmp(&(&b.input.groups, b.input.start))
+            });
+            let mut items: Vec<SearchResultItem> = done.into_iter().flat_map(|s| s.items).collect();
+            if query.stitched && !items.iter().all(|item| item.get("@ptr").is_none()) {
+                let mut seen = HashSet::new();
+                items.retain(|item| match item.get("@ptr") {
+                    Some(ptr) => seen.insert(ptr.to_owned()),
+                    None => true,
+                });
+                match query.sort {
+                    Some(true) => items.sort_by(|a, b| b.timestamp().cmp(a.timestamp())),
+                    Some(false) => items.sort_by(|a, b| a.timestamp().cmp(b.timestamp())),
+                    None => {}
+                }
+                statistics.records_matched = items.len() as f64;
+                if let Some(limit) = query.limit {
+                    items.truncate(limit);
+                }
+            }
+            return Ok(SearchResult::Complete(items, statistics));
+        }
+
+        let mut items = std::mem::take(&mut query.fresh);
+        for slice in query.running.iter() {
+            items.extend(slice.items.iter().cloned());
+        }
+        queries.insert(id, query);
+        Ok(SearchResult::Running(query_id.clone(), items, statistics))
+    }
+
+    async fn stop_query(&self, id: &QueryId) -> Result<()> {
+        let id: String = id.into();
+        let query = match self.lock()?.remove(&id) {
+            Some(query) => query,
+            None => return Ok(()),
+        };
+        let stopped = join_all(query.running.iter().map(|s| self.inner.stop_query(&s.id))).await;
+        stopped.into_iter().collect()
+    }
+}
+
+#[async_trait]
+impl<C: GroupsClient + Send + Sync> GroupsClient for SlicingClient<C> {
+    async fn get_group_names(&self) -> Result<GetGroupsOutput> {
+        self.inner.get_group_names().await
+    }
+
+    async fn get_streams(&self, group_name: &str, since: usize) -> Result<GetStreamsOutput> {
+        self.inner.get_streams(group_name, since).await
+    }
+}
+
+#[async_trait]
+impl<C: FilterLogClient + Send + Sync> FilterLogClient for SlicingClient<C> {
+    async fn filter_logs(&self, input: FilterLogsInput) -> Result<FilterOutput> {
+        self.inner.filter_logs(input).await
+    }
+}
+
+#[async_trait]
+impl<C: LogEventsClient + Send + Sync> LogEventsClient for SlicingClient<C> {
+    async fn get_log_events(&self, input: LogEventsInput) -> Result<LogEventsOutput> {
+        self.inner.get_log_events(input).await
+    }
+}
+
+#[async_trait]
+impl<C: QueryDefinitionsClient + Send + Sync> QueryDefinitionsClient for SlicingClient<C> {
+    async fn get_query_definitions(&self) -> Result<Vec<QueryDefinition>> {
+        self.inner.get_query_definitions().await
+    }
+
+    async fn put_query_definition(&self, input: QueryDefinition) -> Result<String> {
+        self.inner.put_query_definition(input).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::local::LocalEvent;
+
+    fn event(group: &str, stream: &str, second: i64) -> LocalEvent {
+        LocalEvent {
+            group: group.to_owned(),
+            stream: stream.to_owned(),
+            timestamp: second * 1000,
+            message: format!("event {} of {}", second, group),
+            id: 0,
+        }
+    }
+
+    fn input(query: &str, groups: Vec<String>, end: i64) -> StartQueryInput {
+        StartQueryInput {
+            start: 0,
+            end,
+            query: query.to_owned(),
+            groups,
+            stream_names: HashMap::new(),
+        }
+    }
+
+    async fn run<C: QueryClient + Send + Sync>(
+        client: &SlicingClient<C>,
+        input: StartQueryInput,
+    ) -> (Vec<SearchResultItem>, QueryStatistics) {
+        let id = client.start_query(input).await.unwrap();
+        for _ in 0..10 {
+            if let SearchResult::Complete(items, statistics) =
+                client.get_query_results(&id).await.unwrap()
+            {
+                return (items, statistics);
+            }
+        }
+        panic!("query {:?} didn't complete", id);
+    }
+
+    #[tokio::test]
+    async fn fans_out_over_batches_of_groups() {
+        let groups: Vec<String> = (0..25).map(|i| format!("/group/{}", i)).collect();
+        let events = groups.iter().map(|g| event(g, "s", 1)).collect();
+        let client = SlicingClient::new(LocalClient::new(events));
+        let (items, statistics) = run(&client, input("fields @message", groups, 10)).await;
+        assert_eq!(items.len(), 25);
+        assert_eq!(statistics.records_matched, 25.0);
+        assert_eq!(statistics.slices, 2);
+    }
+
+    #[tokio::test]
+    async fn splits_queries_that_hit_the_row_limit() {
+        let events = (0..25_000).map(|s| event("/app", "s", s)).collect();
+        let client = SlicingClient::new(LocalClient::new(events));
+        let (items, statistics) = run(
+            &client,
+            input(
+                "fields @message | sort @timestamp desc",
+                vec!["/app".to_owned()],
+                25_000,
+            ),
+        )
+        .await;
+        assert_eq!(items.len(), 25_000);
+        assert_eq!(statistics.records_matched, 25_000.0);
+        assert_eq!(statistics.slices, 4);
+        assert_eq!(items[0].message(), "event 24999 of /app");
+        assert_eq!(items[24_999].message(), "event 0 of /app");
+        let ptrs: HashSet<&str> = items.iter().filter_map(|item| item.get("@ptr")).collect();
+        assert_eq!(ptrs.len(), 25_000);
+    }
+
+    #[tokio::test]
+    async fn keeps_slices_in_time_order_without_a_timestamp_sort() {
+        let events = (0..25_000).map(|s| event("/app", "s", s)).collect();
+        let client = SlicingClient::new(LocalClient::new(events));
+        let (items, statistics) = run(
+            &client,
+            input(
+                "fields @message | sort @message desc | limit 12000",
+                vec!["/app".to_owned()],
+                25_000,
+            ),
+        )
+        .await;
+        assert!(statistics.slices > 1);
+        assert_eq!(items.len(), 12_000);
+        // Each slice is sorted on its own, the slices follow each other in time.
+        assert!(items[0].timestamp() < items[11_999].timestamp());
+    }
+
+    #[test]
+    fn finds_the_timestamp_sort_and_limit() {
+        assert_eq!(timestamp_sort(&default_query("ERROR | WARN")), Some(true));
+        assert_eq!(
+            timestamp_sort("fields @message | SORT @timestamp asc | limit 5"),
+            Some(false)
+        );
+        assert_eq!(timestamp_sort("sort @timestamp desc | sort @message"), None);
+        assert_eq!(timestamp_sort("fields @message"), None);
+        assert_eq!(query_limit("fields @message | limit 5"), Some(5));
+        assert_eq!(
+            query_limit("fields @message | limit 5 | sort @message"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_split_aggregates() {
+        let events = (0..12_000).map(|s| event("/app", "s", s)).collect();
+        let client = SlicingClient::new(LocalClient::new(events));
+        let (items, statistics) = run(
+            &client,
+            input("stats count(*) by bin(1s)", vec!["/app".to_owned()], 12_000),
+        )
+        .await;
+        assert_eq!(items.len(), DEFAULT_LIMIT as usize);
+        assert_eq!(statistics.slices, 0);
+    }
+
+    #[tokio::test]
+    async fn restricts_groups_to_their_streams() {
+        let events = vec![
+            event("/app", "a", 1),
+            event("/app", "b", 2),
+            event("/other", "c", 3),
+        ];
+        let client = SlicingClient::new(LocalClient::new(events));
+        let mut input = input(
+            "fields @message | sort @timestamp desc",
+            vec!["/app".to_owned(), "/other".to_owned()],
+            10,
+        );
+        input
+            .stream_names
+            .insert("/app".to_owned(), vec!["a".to_owned()]);
+        let (items, _) = run(&client, input).await;
+        let streams: Vec<&str> = items
+            .iter()
+            .filter_map(|item| item.get("@logStream"))
+            .collect();
+        assert_eq!(streams, vec!["c", "a"]);
+    }
+
+    /// Records the stopped queries, and fails the ones of the group `/bad`.
+    #[derive(Debug, Clone)]
+    struct Recorder {
+        inner: LocalClient,
+        fail_start: bool,
+        bad: Arc<Mutex<HashSet<String>>>,
+        started: Arc<AtomicUsize>,
+        stopped: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn new(events: Vec<LocalEvent>, fail_start: bool) -> Self {
+            Self {
+                inner: LocalClient::new(events),
+                fail_start,
+                bad: Arc::default(),
+                started: Arc::default(),
+                stopped: Arc::default(),
+            }
+        }
+
+        fn stopped(&self) -> Vec<String> {
+            let mut stopped = self.stopped.lock().unwrap().clone();
+            stopped.sort();
+            stopped
+        }
+    }
+
+    #[async_trait]
+    impl QueryClient for Recorder {
+        async fn start_query(&self, input: StartQueryInput) -> Result<QueryId> {
+            let bad = input.groups.iter().any(|g| g == "/bad");
+            if bad && self.fail_start {
+                bail!("failed to start.");
+            }
+            let id = self.inner.start_query(input).await?;
+            self.started.fetch_add(1, Ordering::SeqCst);
+            if bad {
+                self.bad.lock().unwrap().insert(String::from(&id));
+            }
+            Ok(id)
+        }
+
+        async fn get_query_results(&self, query_id: &QueryId) -> Result<SearchResult> {
+            if self.bad.lock().unwrap().contains(&String::from(query_id)) {
+                bail!("failed to get results.");
+            }
+            self.inner.get_query_results(query_id).await
+        }
+
+        async fn stop_query(&self, id: &QueryId) -> Result<()> {
+            self.stopped.lock().unwrap().push(String::from(id));
+            self.inner.stop_query(id).await
+        }
+    }
+
+    /// One query per group, `/bad` first.
+    fn restricted_input() -> StartQueryInput {
+        let groups: Vec<String> = vec!["/bad".to_owned(), "/a".to_owned(), "/b".to_owned()];
+        let mut input = input("fields @message", groups.clone(), 10);
+        for group in groups {
+            input.stream_names.insert(group, vec!["s".to_owned()]);
+        }
+        input
+    }
+
+    #[tokio::test]
+    async fn stops_slices_that_hit_the_row_limit() {
+        let events = (0..25_000).map(|s| event("/app", "s", s)).collect();
+        let recorder = Recorder::new(events, false);
+        let client = SlicingClient::new(recorder.clone());
+        run(
+            &client,
+            input("fields @message", vec!["/app".to_owned()], 25_000),
+        )
+        .await;
+        assert_eq!(recorder.stopped(), vec!["local-0"]);
+    }
+
+    #[tokio::test]
+    async fn stops_started_slices_when_one_fails_to_start() {
+        let recorder = Recorder::new(vec![], true);
+        let client = SlicingClient::new(recorder.clone());
+        assert!(client.start_query(restricted_input()).await.is_err());
+        assert_eq!(recorder.stopped(), vec!["local-0", "local-1"]);
+    }
+
+    #[tokio::test]
+    async fn stops_running_slices_when_one_fails() {
+        let recorder = Recorder::new(vec![], false);
+        let client = SlicingClient::new(recorder.clone());
+        let id = client.start_query(restricted_input()).await.unwrap();
+        assert!(client.get_query_results(&id).await.is_err());
+        assert_eq!(recorder.stopped(), vec!["local-0", "local-1", "local-2"]);
+        assert!(client.get_query_results(&id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_stats_queries_that_would_fan_out() {
+        let groups: Vec<String> = (0..25).map(|i| format!("/group/{}", i)).collect();
+        let events = groups.iter().map(|g| event(g, "s", 1)).collect();
+        let recorder = Recorder::new(events, false);
+        let client = SlicingClient::new(recorder.clone());
+        let e = client
+            .start_query(input("stats count(*) by bin(5m)", groups.clone(), 10))
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("needs 2 queries"));
+
+        let mut restricted = restricted_input();
+        restricted.query = "filter @message like /x/ | STATS count(*) by @log".to_owned();
+        assert!(client.start_query(restricted).await.is_err());
+        assert_eq!(recorder.started.load(Ordering::SeqCst), 0);
+
+        let (items, _) = run(
+            &client,
+            input("stats count(*) by @log", groups[..20].to_vec(), 10),
+        )
+        .await;
+        assert_eq!(items.len(), 20);
+        assert_eq!(recorder.started.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn forgets_stopped_queries() {
+        let client = SlicingClient::new(LocalClient::new(vec![event("/app", "a", 1)]));
+        let id = client
+            .start_query(input("fields @message", vec!["/app".to_owned()], 10))
+            .await
+            .unwrap();
+        client.stop_query(&id).await.unwrap();
+        assert!(client.get_query_results(&id).await.is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use async_trait::async_trait;
 
@@ -21,56 +23,193 @@ pub struct GetGroupsOutput {
     pub items: Vec<String>,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct LogStream {
+    pub name: String,
+    /// Milliseconds since the epoch.
+    pub last_event_time: Option<i64>,
+    /// Milliseconds since the epoch.
+    pub last_ingestion_time: Option<i64>,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct GetStreamsOutput {
-    pub items: Vec<String>,
+    /// Ordered by last event time, newest first.
+    pub items: Vec<LogStream>,
 }
 
 #[async_trait]
 pub trait GroupsClient {
     async fn get_group_names(&self) -> Result<GetGroupsOutput>;
+    /// Streams that had events ingested since `since`, in milliseconds since the epoch.
     async fn get_streams(&self, group_name: &str, since: usize) -> Result<GetStreamsOutput>;
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct SearchResultItem {
-    pub message: String,
-    pub timestamp: String,
+    /// Every field returned for the row, in the order Logs Insights returned them.
+    pub fields: Vec<(String, String)>,
+}
+
+impl SearchResultItem {
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn timestamp(&self) -> &str {
+        self.get("@timestamp").unwrap_or_default()
+    }
+
+    pub fn message(&self) -> &str {
+        self.get("@message").unwrap_or_default()
+    }
+}
+
+/// How much a Logs Insights query has scanned and matched so far.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct QueryStatistics {
+    pub records_matched: f64,
+    pub records_scanned: f64,
+    pub bytes_scanned: f64,
+    /// How many sub-queries the results were stitched together from, when the
+    /// query was split to get past the row limit.
+    pub slices: usize,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum SearchResult {
-    Running(QueryId),
-    Complete(Vec<SearchResultItem>),
+    /// Rows matched so far, which Logs Insights returns while the query runs.
+    Running(QueryId, Vec<SearchResultItem>, QueryStatistics),
+    Complete(Vec<SearchResultItem>, QueryStatistics),
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct StartQueryInput {
     pub start: i64,
     pub end: i64,
-    pub filter: String,
+    /// Logs Insights query string, sent to StartQuery as is.
+    pub query: String,
     pub groups: Vec<String>,
+    /// Streams to restrict each group to, groups without an entry are searched in full.
+    /// `SlicingClient` turns these into `@logStream` filters.
+    pub stream_names: HashMap<String, Vec<String>>,
 }
 
 #[async_trait]
 pub trait QueryClient {
-    async fn start_default_query<'a>(&self, input: StartQueryInput) -> Result<QueryId>;
-    async fn get_default_query_results(&self, query_id: &QueryId) -> Result<SearchResult>;
+    async fn start_query(&self, input: StartQueryInput) -> Result<QueryId>;
+    async fn get_query_results(&self, query_id: &QueryId) -> Result<SearchResult>;
     async fn stop_query(&self, id: &QueryId) -> Result<()>;
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct FilterLogsInput {
+    pub start: i64,
+    pub end: i64,
+    pub groups: Vec<String>,
+    pub stream_name_prefix: Option<String>,
+    /// Streams to restrict each group to, groups without an entry are searched in full.
+    pub stream_names: HashMap<String, Vec<String>>,
+    /// CloudWatch Logs filter pattern, e.g. `ERROR -healthcheck` or `{ $.level = "error" }`.
+    pub filter_pattern: String,
+    /// The page to fetch, the first one of the first group by default.
+    pub cursor: FilterLogsCursor,
+}
+
+/// Where a search continues: the group in `FilterLogsInput::groups` and its page token.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct FilterLogsCursor {
+    pub group: usize,
+    pub next_token: Option<String>,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct FilterOutputItem {
+    pub event_id: String,
+    pub group: String,
+    pub stream: String,
     pub message: String,
-    pub timestamp: String,
+    /// Milliseconds since the epoch.
+    pub timestamp: i64,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct FilterOutput {
+    /// Events of a single page, newest first.
     pub items: Vec<FilterOutputItem>,
+    /// The next page, unless this was the last one.
+    pub next: Option<FilterLogsCursor>,
 }
 
 #[async_trait]
 pub trait FilterLogClient {
-    async fn filter_logs(&self) -> Result<FilterOutput>;
+    /// Fetch the page of events at `input.cursor`.
+    async fn filter_logs(&self, input: FilterLogsInput) -> Result<FilterOutput>;
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LogEventsInput {
+    pub group: String,
+    pub stream: String,
+    /// Milliseconds since the epoch of the event to fetch the context of.
+    pub timestamp: i64,
+    /// How many events to fetch on each side.
+    pub limit: i32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LogEvent {
+    /// Milliseconds since the epoch.
+    pub timestamp: i64,
+    pub message: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LogEventsOutput {
+    /// Events before the timestamp, oldest first.
+    pub before: Vec<LogEvent>,
+    /// Events at and after the timestamp, oldest first.
+    pub after: Vec<LogEvent>,
+}
+
+#[async_trait]
+pub trait LogEventsClient {
+    /// Events of a single stream around a timestamp, like `grep -C`.
+    async fn get_log_events(&self, input: LogEventsInput) -> Result<LogEventsOutput>;
+}
+
+impl From<FilterOutputItem> for SearchResultItem {
+    fn from(item: FilterOutputItem) -> Self {
+        SearchResultItem {
+            fields: vec![
+                (
+                    "@timestamp".to_owned(),
+                    super::format_timestamp(item.timestamp),
+                ),
+                ("@message".to_owned(), item.message),
+                ("@log".to_owned(), item.group),
+                ("@logStream".to_owned(), item.stream),
+            ],
+        }
+    }
+}
+
+/// A saved Logs Insights query, shared with the CloudWatch console.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QueryDefinition {
+    /// Set for definitions that exist in CloudWatch Logs.
+    pub id: Option<String>,
+    pub name: String,
+    pub query: String,
+    pub groups: Vec<String>,
+}
+
+#[async_trait]
+pub trait QueryDefinitionsClient {
+    async fn get_query_definitions(&self) -> Result<Vec<QueryDefinition>>;
+    /// Create the definition, or update the one with the same name. Returns its id.
+    async fn put_query_definition(&self, input: QueryDefinition) -> Result<String>;
 }
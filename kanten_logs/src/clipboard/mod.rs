@@ -0,0 +1,150 @@
+use std::{
+    env, fmt,
+    io::{stdout, Write},
+    process::{Command, Stdio},
+};
+
+use anyhow::{anyhow, bail, Result};
+
+/// Terminals cap OSC 52 payloads, xterm and hterm at about this many base64 bytes.
+const MAX_OSC52_LEN: usize = 74_994;
+
+/// How the text reached the clipboard.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CopyMethod {
+    Osc52,
+    Command(&'static str),
+}
+
+impl fmt::Display for CopyMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopyMethod::Osc52 => write!(f, "OSC 52"),
+            CopyMethod::Command(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Copy the text with a clipboard command, falling back to an OSC 52 escape sequence.
+/// Over SSH the commands would copy on the remote host, so the escape sequence, which
+/// also works in tmux, is tried first there.
+pub fn copy(text: &str) -> Result<CopyMethod> {
+    let over_ssh = env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some();
+    let mut failed = None;
+    if !over_ssh {
+        match copy_with_command(text) {
+            Ok(method) => return Ok(method),
+            Err(e) => failed = Some(e),
+        }
+    }
+    let encoded = base64(text.as_bytes());
+    let term = env::var("TERM").unwrap_or_default();
+    if encoded.len() <= MAX_OSC52_LEN && !matches!(term.as_str(), "" | "dumb" | "linux") {
+        match write_osc52(&encoded, &term) {
+            Ok(()) => return Ok(CopyMethod::Osc52),
+            Err(e) => log::debug!("failed to write OSC 52: {:?}", e),
+        }
+    }
+    match failed {
+        Some(e) => Err(e),
+        None => copy_with_command(text),
+    }
+}
+
+fn write_osc52(encoded: &str, term: &str) -> Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", encoded);
+    // tmux and screen only pass escape sequences through to the terminal when wrapped.
+    let sequence = if env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else if term.starts_with("screen") {
+        format!("\x1bP{}\x1b\\", sequence)
+    } else {
+        sequence
+    };
+    let mut out = stdout();
+    out.write_all(sequence.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Clipboard commands worth trying on this platform, in order.
+fn commands() -> Vec<(&'static str, &'static [&'static str])> {
+    let mut commands: Vec<(&'static str, &'static [&'static str])> = vec![];
+    if cfg!(target_os = "macos") {
+        commands.push(("pbcopy", &[]));
+    }
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        commands.push(("wl-copy", &[]));
+    }
+    if env::var_os("DISPLAY").is_some() {
+        commands.push(("xclip", &["-selection", "clipboard"]));
+        commands.push(("xsel", &["--clipboard", "--input"]));
+    }
+    // WSL
+    commands.push(("clip.exe", &[]));
+    commands
+}
+
+fn copy_with_command(text: &str) -> Result<CopyMethod> {
+    let commands = commands();
+    for (name, args) in commands.iter() {
+        let mut child = match Command::new(name)
+            .args(args.iter())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(_) => continue,
+        };
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("failed to open stdin of {}.", name))?
+            .write_all(text.as_bytes())?;
+        if child.wait()?.success() {
+            return Ok(CopyMethod::Command(name));
+        }
+    }
+    let names: Vec<&str> = commands.iter().map(|(name, _)| *name).collect();
+    bail!(
+        "the terminal can't take the text and no clipboard command worked (tried {}).",
+        names.join(", ")
+    )
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len() * 4 / 3 + 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - i * 8));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_base64_with_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64("ログ".as_bytes()), "44Ot44Kw");
+        assert_eq!(base64(&[0xff, 0xfe, 0x00]), "//4A");
+    }
+}
@@ -0,0 +1,143 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use super::BlockComponent;
+use crate::client::{format_timestamp, LogEvent, LogEventsInput, LogEventsOutput};
+use crate::keymap::Action;
+
+/// Events before and after a log event in its stream, like `grep -C`.
+#[derive(Debug, Clone)]
+pub struct ContextModel {
+    pub input: LogEventsInput,
+    /// Message of the event, to tell it from others in the same millisecond.
+    message: String,
+    pub items: Vec<LogEvent>,
+    /// Index of the event the context was opened for.
+    pub target: Option<usize>,
+    pub state: ListState,
+    pub loading: bool,
+}
+
+impl ContextModel {
+    pub fn new(input: LogEventsInput, message: impl Into<String>) -> Self {
+        Self {
+            input,
+            message: message.into(),
+            items: vec![],
+            target: None,
+            state: ListState::default(),
+            loading: true,
+        }
+    }
+
+    pub fn set_events(&mut self, output: LogEventsOutput) {
+        let timestamp = self.input.timestamp;
+        let at = output
+            .after
+            .iter()
+            .position(|e| e.timestamp == timestamp && e.message.trim() == self.message.trim())
+            .or_else(|| output.after.iter().position(|e| e.timestamp == timestamp));
+        self.target = at.map(|i| output.before.len() + i);
+        self.items = output.before;
+        self.items.extend(output.after);
+        self.state.select(
+            self.target
+                .or(if self.items.is_empty() { None } else { Some(0) }),
+        );
+        self.loading = false;
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::Down => self.select(1),
+            Action::Up => self.select(-1),
+            Action::BackToEvent => self
+                .state
+                .select(self.target.or_else(|| self.state.selected())),
+            _ => {}
+        }
+    }
+
+    fn select(&mut self, delta: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = self.state.selected().unwrap_or_default() as isize + delta;
+        self.state
+            .select(Some(i.max(0).min(self.items.len() as isize - 1) as usize));
+    }
+}
+
+pub struct ContextView<'a> {
+    model: &'a mut ContextModel,
+    block: Option<Block<'a>>,
+    highlight_style: Style,
+}
+
+impl<'a> ContextView<'a> {
+    pub fn new(model: &'a mut ContextModel) -> Self {
+        Self {
+            model,
+            block: None,
+            highlight_style: Style::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+}
+
+impl<'a> BlockComponent for ContextView<'a> {
+    fn draw<B: Backend>(self, f: &mut Frame<B>, area: Rect) {
+        f.render_widget(Clear, area);
+        let target = self.model.target;
+        let items: Vec<ListItem> = if self.model.loading {
+            vec![ListItem::new("Loading...")]
+        } else if self.model.items.is_empty() {
+            vec![ListItem::new("No events found in the stream.")]
+        } else {
+            self.model
+                .items
+                .iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    let style = if Some(i) == target {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    };
+                    ListItem::new(Spans::from(vec![
+                        Span::styled(
+                            format_timestamp(e.timestamp),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::raw(" "),
+                        Span::styled(e.message.trim_end().replace('\n', " "), style),
+                    ]))
+                })
+                .collect()
+        };
+        let mut list = List::new(items)
+            .highlight_style(self.highlight_style)
+            .highlight_symbol("▸");
+        if let Some(block) = self.block {
+            list = list.block(block);
+        }
+        f.render_stateful_widget(list, area, &mut self.model.state);
+    }
+}
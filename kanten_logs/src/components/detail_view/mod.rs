@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+use tui::{
+    backend::Backend,
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Clear, Widget},
+    Frame,
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{BlockComponent, LogListItem};
+use crate::keymap::Action;
+
+const INDENT: usize = 2;
+
+/// One line of the detail pane, before wrapping.
+#[derive(Debug, Clone)]
+struct DetailLine {
+    indent: usize,
+    spans: Vec<(String, Style)>,
+    /// Path of the JSON object or array opened on this line, which can be collapsed.
+    path: Option<String>,
+    /// Field or JSON value on this line, copied with `y`.
+    value: Option<String>,
+}
+
+/// All fields of a single event, with the message pretty-printed when it is JSON.
+#[derive(Debug, Clone)]
+pub struct DetailModel {
+    fields: Vec<(String, String)>,
+    json: Option<Value>,
+    collapsed: HashSet<String>,
+    lines: Vec<DetailLine>,
+    cursor: usize,
+    /// First visible row, after wrapping.
+    offset: usize,
+}
+
+impl DetailModel {
+    pub fn new(item: &LogListItem) -> Self {
+        let fields = item.fields();
+        let json = fields
+            .iter()
+            .find(|(n, _)| n == "@message")
+            .and_then(|(_, m)| parse_json(m));
+        let mut model = Self {
+            fields,
+            json,
+            collapsed: HashSet::new(),
+            lines: vec![],
+            cursor: 0,
+            offset: 0,
+        };
+        model.build_lines();
+        model
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.json.is_some()
+    }
+
+    fn build_lines(&mut self) {
+        let mut lines = vec![];
+        let name_width = self
+            .fields
+            .iter()
+            .map(|(n, _)| n.width())
+            .max()
+            .unwrap_or_default();
+        for (name, value) in self.fields.iter() {
+            if name == "@message" && self.json.is_some() {
+                continue;
+            }
+            let name = format!("{:width$}  ", name, width = name_width);
+            let mut value_lines = value.lines();
+            lines.push(DetailLine {
+                indent: 0,
+                spans: vec![
+                    (name, key_style()),
+                    (
+                        value_lines.next().unwrap_or_default().to_owned(),
+                        Style::default(),
+                    ),
+                ],
+                path: None,
+                value: Some(value.clone()),
+            });
+            for line in value_lines {
+                lines.push(DetailLine {
+                    indent: 0,
+                    spans: vec![
+                        (" ".repeat(name_width + 2), Style::default()),
+                        (line.to_owned(), Style::default()),
+                    ],
+                    path: None,
+                    value: Some(value.clone()),
+                });
+            }
+        }
+        if let Some(ref json) = self.json {
+            lines.push(DetailLine {
+                indent: 0,
+                spans: vec![("@message".to_owned(), key_style())],
+                path: None,
+                value: self
+                    .fields
+                    .iter()
+                    .find(|(n, _)| n == "@message")
+                    .map(|(_, m)| m.clone()),
+            });
+            push_value(
+                &mut lines,
+                &self.collapsed,
+                json,
+                None,
+                "$".to_owned(),
+                1,
+                false,
+            );
+        }
+        self.lines = lines;
+        self.cursor = self.cursor.min(self.lines.len().saturating_sub(1));
+    }
+
+    /// Field or JSON value under the cursor, strings without quotes.
+    pub fn cursor_value(&self) -> Option<&str> {
+        self.lines.get(self.cursor).and_then(|l| l.value.as_deref())
+    }
+
+    fn next(&mut self) {
+        if self.cursor + 1 < self.lines.len() {
+            self.cursor += 1;
+        }
+    }
+
+    fn cursor_path(&self) -> Option<&String> {
+        self.lines.get(self.cursor).and_then(|l| l.path.as_ref())
+    }
+
+    fn toggle(&mut self) {
+        if let Some(path) = self.cursor_path().cloned() {
+            if !self.collapsed.remove(&path) {
+                self.collapsed.insert(path);
+            }
+            self.build_lines();
+        }
+    }
+
+    fn set_collapsed(&mut self, collapsed: bool) {
+        if let Some(path) = self.cursor_path().cloned() {
+            if collapsed {
+                self.collapsed.insert(path);
+            } else {
+                self.collapsed.remove(&path);
+            }
+            self.build_lines();
+        }
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::Down => self.next(),
+            Action::Up => self.cursor = self.cursor.saturating_sub(1),
+            Action::Select => self.toggle(),
+            Action::Left => self.set_collapsed(true),
+            Action::Right => self.set_collapsed(false),
+            _ => {}
+        }
+    }
+}
+
+/// Parse the message as JSON, also when it follows a plain text prefix such as
+/// Lambda's `timestamp\trequest id\tlevel\t`.
+fn parse_json(message: &str) -> Option<Value> {
+    let message = message.trim();
+    let candidates = [Some(0), message.find('{')];
+    candidates.iter().flatten().find_map(|start| {
+        match serde_json::from_str::<Value>(&message[*start..]) {
+            Ok(v) if v.is_object() || v.is_array() => Some(v),
+            _ => None,
+        }
+    })
+}
+
+fn key_style() -> Style {
+    Style::default()
+        .fg(Color::Blue)
+        .add_modifier(Modifier::BOLD)
+}
+
+fn value_style(value: &Value) -> Style {
+    match value {
+        Value::String(_) => Style::default().fg(Color::Green),
+        Value::Number(_) => Style::default().fg(Color::Cyan),
+        Value::Bool(_) => Style::default().fg(Color::Magenta),
+        Value::Null => Style::default().fg(Color::DarkGray),
+        _ => Style::default(),
+    }
+}
+
+fn push_value(
+    lines: &mut Vec<DetailLine>,
+    collapsed: &HashSet<String>,
+    value: &Value,
+    key: Option<&str>,
+    path: String,
+    indent: usize,
+    comma: bool,
+) {
+    let copied = match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    };
+    let mut spans = vec![];
+    if let Some(key) = key {
+        spans.push((format!("{:?}", key), key_style()));
+        spans.push((": ".to_owned(), Style::default()));
+    }
+    let comma = if comma { "," } else { "" };
+    let (open, close, len) = match value {
+        Value::Object(map) => ("{", "}", map.len()),
+        Value::Array(items) => ("[", "]", items.len()),
+        _ => {
+            spans.push((value.to_string(), value_style(value)));
+            spans.push((comma.to_owned(), Style::default()));
+            lines.push(DetailLine {
+                indent,
+                spans,
+                path: None,
+                value: Some(copied),
+            });
+            return;
+        }
+    };
+    if len == 0 {
+        spans.push((format!("{}{}{}", open, close, comma), Style::default()));
+        lines.push(DetailLine {
+            indent,
+            spans,
+            path: None,
+            value: Some(copied),
+        });
+        return;
+    }
+    if collapsed.contains(&path) {
+        spans.push((format!("{} … {}{}", open, close, comma), Style::default()));
+        spans.push((
+            format!("  {} {}", len, if len == 1 { "item" } else { "items" }),
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines.push(DetailLine {
+            indent,
+            spans,
+            path: Some(path),
+            value: Some(copied),
+        });
+        return;
+    }
+    spans.push((open.to_owned(), Style::default()));
+    lines.push(DetailLine {
+        indent,
+        spans,
+        path: Some(path.clone()),
+        value: Some(copied),
+    });
+    match value {
+        Value::Object(map) => {
+            for (i, (k, v)) in map.iter().enumerate() {
+                let child = format!("{}.{}", path, k);
+                push_value(lines, collapsed, v, Some(k), child, indent + 1, i + 1 < len);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                let child = format!("{}[{}]", path, i);
+                push_value(lines, collapsed, v, None, child, indent + 1, i + 1 < len);
+            }
+        }
+        _ => {}
+    }
+    lines.push(DetailLine {
+        indent,
+        spans: vec![(format!("{}{}", close, comma), Style::default())],
+        path: None,
+        value: None,
+    });
+}
+
+pub struct DetailView<'a> {
+    model: &'a mut DetailModel,
+    block: Option<Block<'a>>,
+    highlight_style: Style,
+}
+
+impl<'a> DetailView<'a> {
+    pub fn new(model: &'a mut DetailModel) -> Self {
+        Self {
+            model,
+            block: None,
+            highlight_style: Style::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+}
+
+impl<'a> BlockComponent for DetailView<'a> {
+    fn draw<B: Backend>(self, f: &mut Frame<B>, area: Rect) {
+        f.render_widget(Clear, area);
+        let area = match self.block {
+            Some(b) => {
+                let inner_area = b.inner(area);
+                f.render_widget(b, area);
+                inner_area
+            }
+            None => area,
+        };
+        f.render_widget(
+            Rows {
+                model: self.model,
+                highlight_style: self.highlight_style,
+            },
+            area,
+        );
+    }
+}
+
+struct Rows<'a> {
+    model: &'a mut DetailModel,
+    highlight_style: Style,
+}
+
+impl<'a> Widget for Rows<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < 1 || area.height < 1 {
+            return;
+        }
+        let model = self.model;
+
+        // Wrap every line to the width, remembering where the cursor line starts and ends.
+        let mut rows: Vec<(usize, Vec<(String, Style)>)> = vec![];
+        let mut cursor_rows = (0, 0);
+        for (i, line) in model.lines.iter().enumerate() {
+            if i == model.cursor {
+                cursor_rows.0 = rows.len();
+            }
+            wrap(line, area.width as usize, &mut rows);
+            if i == model.cursor {
+                cursor_rows.1 = rows.len();
+            }
+        }
+
+        let height = area.height as usize;
+        if cursor_rows.0 < model.offset {
+            model.offset = cursor_rows.0;
+        } else if cursor_rows.1 > model.offset + height {
+            model.offset = (cursor_rows.1 - height).min(cursor_rows.0);
+        }
+
+        for (y, (row, (indent, spans))) in rows
+            .iter()
+            .enumerate()
+            .skip(model.offset)
+            .take(height)
+            .enumerate()
+        {
+            let y = area.y + y as u16;
+            let highlighted = (cursor_rows.0..cursor_rows.1).contains(&row);
+            if highlighted {
+                buf.set_style(Rect::new(area.x, y, area.width, 1), self.highlight_style);
+            }
+            let mut x = area.x + *indent as u16;
+            for (text, style) in spans {
+                let style = if highlighted {
+                    style.patch(self.highlight_style)
+                } else {
+                    *style
+                };
+                let (next_x, _) = buf.set_stringn(x, y, text, (area.right() - x) as usize, style);
+                x = next_x;
+            }
+        }
+    }
+}
+
+/// Split a line into rows no wider than `width`, continuing rows at the line's indent.
+fn wrap(line: &DetailLine, width: usize, rows: &mut Vec<(usize, Vec<(String, Style)>)>) {
+    let indent = (line.indent * INDENT).min(width.saturating_sub(1));
+    let width = width - indent;
+    let mut row: Vec<(String, Style)> = vec![];
+    let mut row_width = 0;
+    for (text, style) in line.spans.iter() {
+        let mut current = String::new();
+        for g in UnicodeSegmentation::graphemes(text.as_str(), true) {
+            let w = g.width();
+            if row_width + w > width && row_width > 0 {
+                row.push((std::mem::take(&mut current), *style));
+                rows.push((indent, std::mem::take(&mut row)));
+                row_width = 0;
+            }
+            current.push_str(g);
+            row_width += w;
+        }
+        row.push((current, *style));
+    }
+    rows.push((indent, row));
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 use tui::widgets::ListState;
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::keymap::Action;
 
 pub struct GroupList {
     pub state: ListState,
@@ -19,15 +19,14 @@ impl GroupList {
     ) -> GroupList {
         let mut state = ListState::default();
         let filter = filter.into();
-        let selected: std::collections::BTreeSet<String> = if !filter.is_empty() && default_select {
-            let re = regex::Regex::new(&filter).expect("Failed to construct Regexp");
-            items
+        let re = regex::Regex::new(&filter).ok();
+        let selected: std::collections::BTreeSet<String> = match re {
+            Some(re) if !filter.is_empty() && default_select => items
                 .iter()
                 .filter(|name| re.is_match(name))
                 .cloned()
-                .collect()
-        } else {
-            BTreeSet::new()
+                .collect(),
+            _ => BTreeSet::new(),
         };
         let filtered: Vec<String> = selected.iter().cloned().collect();
         state.select(Some(0));
@@ -41,10 +40,11 @@ impl GroupList {
         }
     }
 
-    pub fn set_filter(&mut self, filter: impl Into<String>) {
+    /// Keeps the previous filtered items when the filter isn't a valid regular expression.
+    pub fn set_filter(&mut self, filter: impl Into<String>) -> Result<(), regex::Error> {
         self.filter = filter.into();
         if !self.filter.is_empty() {
-            let re = regex::Regex::new(&self.filter).expect("Failed to construct Regexp");
+            let re = regex::Regex::new(&self.filter)?;
             self.filtered = self
                 .items
                 .iter()
@@ -54,6 +54,19 @@ impl GroupList {
         } else {
             self.filtered = self.items.clone();
         };
+        Ok(())
+    }
+
+    /// Select the groups matching `pattern` (regular expression) instead of the current ones.
+    pub fn select_matching(&mut self, pattern: &str) -> Result<(), regex::Error> {
+        let re = regex::Regex::new(pattern)?;
+        self.selected = self
+            .items
+            .iter()
+            .filter(|name| re.is_match(name))
+            .cloned()
+            .collect();
+        Ok(())
     }
 
     // pub fn clear(&mut self) {
@@ -76,13 +89,10 @@ impl GroupList {
     //         .collect()
     // }
 
-    pub fn on_key(&mut self, key: KeyEvent) {
-        match key {
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-            } => {
-                log::debug!("on group_list key {:?}", self.state.selected());
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::Select => {
+                log::debug!("on group_list select {:?}", self.state.selected());
                 if let Some(index) = self.state.selected() {
                     let name = &self.filtered[index];
                     log::debug!("name {:?}", name);
@@ -93,24 +103,8 @@ impl GroupList {
                     }
                 }
             }
-            // down
-            KeyEvent {
-                code: KeyCode::Char('n'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Down,
-                modifiers: KeyModifiers::NONE,
-            } => self.next(),
-            // up
-            KeyEvent {
-                code: KeyCode::Char('p'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Up,
-                modifiers: KeyModifiers::NONE,
-            } => self.previous(),
+            Action::Down => self.next(),
+            Action::Up => self.previous(),
             _ => {}
         }
     }
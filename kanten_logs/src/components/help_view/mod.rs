@@ -0,0 +1,147 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use super::BlockComponent;
+use crate::keymap::{Action, Context, Keymap};
+
+#[derive(Debug, Clone)]
+enum HelpLine {
+    Section(Context),
+    Binding { keys: String, action: Action },
+}
+
+/// Bindings of the contexts the keys of the focused pane are looked up in.
+#[derive(Debug, Clone)]
+pub struct HelpModel {
+    pub title: String,
+    lines: Vec<HelpLine>,
+    scroll: u16,
+    /// Lines that fit, as of the last draw.
+    height: u16,
+}
+
+impl HelpModel {
+    pub fn new(title: impl Into<String>, keymap: &Keymap, contexts: &[Context]) -> Self {
+        let mut lines = vec![];
+        for context in contexts.iter() {
+            lines.push(HelpLine::Section(*context));
+            lines.extend(keymap.bindings(*context).map(|(action, keys)| {
+                let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+                HelpLine::Binding {
+                    keys: keys.join(", "),
+                    action: *action,
+                }
+            }));
+        }
+        Self {
+            title: title.into(),
+            lines,
+            scroll: 0,
+            height: 0,
+        }
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        let max = (self.lines.len() as u16).saturating_sub(self.height);
+        let page = self.height.max(1);
+        self.scroll = match action {
+            Action::Down => self.scroll.saturating_add(1),
+            Action::Up => self.scroll.saturating_sub(1),
+            Action::PageDown => self.scroll.saturating_add(page),
+            Action::PageUp => self.scroll.saturating_sub(page),
+            _ => self.scroll,
+        }
+        .min(max);
+    }
+}
+
+pub struct HelpView<'a> {
+    model: &'a mut HelpModel,
+    block: Option<Block<'a>>,
+    key_style: Style,
+}
+
+impl<'a> HelpView<'a> {
+    pub fn new(model: &'a mut HelpModel) -> Self {
+        Self {
+            model,
+            block: None,
+            key_style: Style::default().fg(Color::Cyan),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+
+impl<'a> BlockComponent for HelpView<'a> {
+    fn draw<B: Backend>(self, f: &mut Frame<B>, area: Rect) {
+        f.render_widget(Clear, area);
+        let inner_area = match self.block {
+            Some(ref b) => b.inner(area),
+            None => area,
+        };
+        self.model.height = inner_area.height;
+        let key_width = self
+            .model
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                HelpLine::Binding { keys, .. } => Some(keys.width()),
+                HelpLine::Section(_) => None,
+            })
+            .max()
+            .unwrap_or_default();
+        let action_width = self
+            .model
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                HelpLine::Binding { action, .. } => Some(action.to_string().len()),
+                HelpLine::Section(_) => None,
+            })
+            .max()
+            .unwrap_or_default();
+        let text: Vec<Spans> = self
+            .model
+            .lines
+            .iter()
+            .map(|line| match line {
+                HelpLine::Section(context) => Spans::from(Span::styled(
+                    format!("[keys.{}]", context),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )),
+                HelpLine::Binding { keys, action } => Spans::from(vec![
+                    Span::styled(
+                        format!(
+                            "  {}{}  ",
+                            keys,
+                            " ".repeat(key_width.saturating_sub(keys.width()))
+                        ),
+                        self.key_style,
+                    ),
+                    Span::styled(
+                        format!("{:<width$}  ", action.to_string(), width = action_width),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(action.description()),
+                ]),
+            })
+            .collect();
+        let mut paragraph = Paragraph::new(text).scroll((self.model.scroll, 0));
+        if let Some(block) = self.block {
+            paragraph = paragraph.block(block);
+        }
+        f.render_widget(paragraph, area);
+    }
+}
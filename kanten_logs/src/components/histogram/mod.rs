@@ -0,0 +1,318 @@
+use tui::{
+    backend::Backend,
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Widget},
+    Frame,
+};
+
+use crate::client::{format_timestamp, parse_timestamp};
+use crate::keymap::Action;
+
+use super::{BlockComponent, LogListItem};
+
+/// Bucket sizes in seconds, the smallest one that fits the width is used.
+const BIN_SECS: [i64; 16] = [
+    1,
+    5,
+    10,
+    30,
+    60,
+    300,
+    600,
+    1800,
+    3600,
+    3 * 3600,
+    6 * 3600,
+    12 * 3600,
+    86400,
+    7 * 86400,
+    30 * 86400,
+    365 * 86400,
+];
+
+const BARS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+
+/// Event counts per time bucket of the queried duration.
+#[derive(Debug, Clone, Default)]
+pub struct HistogramModel {
+    range: Option<(i64, i64)>,
+    /// Event times in seconds.
+    timestamps: Vec<i64>,
+    width: u16,
+    bin_secs: i64,
+    first_bin: i64,
+    bins: Vec<u64>,
+    selected: Option<usize>,
+}
+
+impl HistogramModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the queried range in seconds, `None` hides the histogram (e.g. while tailing).
+    pub fn set_range(&mut self, range: Option<(i64, i64)>) {
+        self.range = range.filter(|(start, end)| start < end);
+        self.clear();
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.range.is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.timestamps.clear();
+        self.selected = None;
+        self.rebin();
+    }
+
+    pub fn push(&mut self, items: &[LogListItem]) {
+        self.timestamps.extend(
+            items
+                .iter()
+                .filter_map(|item| parse_timestamp(item.timestamp()))
+                .map(|millis| millis.div_euclid(1000)),
+        );
+        self.rebin();
+    }
+
+    fn resize(&mut self, width: u16) {
+        if self.width != width {
+            self.width = width;
+            self.rebin();
+        }
+    }
+
+    fn rebin(&mut self) {
+        self.bins.clear();
+        let (start, end) = match self.range {
+            Some(range) => range,
+            None => return,
+        };
+        let width = self.width.max(1) as i64;
+        // Buckets are aligned to their size, so the first one may start before `start`.
+        let len = |secs: i64| (end - start.div_euclid(secs) * secs + secs - 1) / secs;
+        self.bin_secs = *BIN_SECS
+            .iter()
+            .find(|secs| len(**secs) <= width)
+            .unwrap_or(&BIN_SECS[BIN_SECS.len() - 1]);
+        self.first_bin = start.div_euclid(self.bin_secs) * self.bin_secs;
+        self.bins = vec![0; len(self.bin_secs).min(width) as usize];
+        for t in self.timestamps.iter() {
+            let i = (t - self.first_bin).div_euclid(self.bin_secs);
+            if i < 0 {
+                continue;
+            }
+            if let Some(count) = self.bins.get_mut(i as usize) {
+                *count += 1;
+            }
+        }
+        if self.selected.map(|i| i >= self.bins.len()) == Some(true) {
+            self.selected = None;
+        }
+    }
+
+    /// Start and end, in seconds, of a bucket.
+    fn bin_range(&self, i: usize) -> (i64, i64) {
+        let start = self.first_bin + i as i64 * self.bin_secs;
+        (start, start + self.bin_secs)
+    }
+
+    /// The selected bucket, clamped to the queried range.
+    pub fn selected_range(&self) -> Option<(i64, i64)> {
+        let (start, end) = self.range?;
+        let (bin_start, bin_end) = self.bin_range(self.selected?);
+        Some((bin_start.max(start), bin_end.min(end)))
+    }
+
+    /// Returns true when the action was handled.
+    pub fn on_action(&mut self, action: Action) -> bool {
+        if self.bins.is_empty() {
+            return false;
+        }
+        let last = self.bins.len() - 1;
+        match action {
+            Action::PrevBucket => {
+                self.selected = Some(self.selected.map_or(last, |i| i.saturating_sub(1)));
+                true
+            }
+            Action::NextBucket => {
+                self.selected = Some(self.selected.map_or(last, |i| (i + 1).min(last)));
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+pub struct Histogram<'a> {
+    model: &'a mut HistogramModel,
+    block: Option<Block<'a>>,
+    bar_style: Style,
+    selected_style: Style,
+    highlight_style: Style,
+    caption_style: Style,
+}
+
+impl<'a> Histogram<'a> {
+    pub fn new(model: &'a mut HistogramModel) -> Self {
+        Self {
+            model,
+            block: None,
+            bar_style: Style::default().fg(Color::Rgb(238, 173, 15)),
+            selected_style: Style::default().fg(Color::White),
+            highlight_style: Style::default(),
+            caption_style: Style::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Background of the selected bucket.
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    /// The range or selection under the bars.
+    pub fn caption_style(mut self, style: Style) -> Self {
+        self.caption_style = style;
+        self
+    }
+}
+
+impl<'a> BlockComponent for Histogram<'a> {
+    fn draw<B: Backend>(self, f: &mut Frame<B>, area: Rect) {
+        let area = match self.block {
+            Some(b) => {
+                let inner_area = b.inner(area);
+                f.render_widget(b, area);
+                inner_area
+            }
+            None => area,
+        };
+        if area.height < 2 || area.width == 0 {
+            return;
+        }
+
+        self.model.resize(area.width);
+        let bars = Bars {
+            model: self.model,
+            bar_style: self.bar_style,
+            selected_style: self.selected_style,
+            highlight_style: self.highlight_style,
+            caption_style: self.caption_style,
+        };
+        f.render_widget(bars, area);
+    }
+}
+
+struct Bars<'a> {
+    model: &'a HistogramModel,
+    bar_style: Style,
+    selected_style: Style,
+    highlight_style: Style,
+    caption_style: Style,
+}
+
+impl<'a> Widget for Bars<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let model = self.model;
+
+        // Bars take every row but the last one, which describes the range or selection.
+        let bar_height = area.height - 1;
+        let max = model.bins.iter().max().copied().unwrap_or_default().max(1);
+        for (i, count) in model.bins.iter().enumerate().take(area.width as usize) {
+            let style = if model.selected == Some(i) {
+                self.selected_style
+            } else {
+                self.bar_style
+            };
+            let x = area.x + i as u16;
+            if model.selected == Some(i) {
+                for y in area.y..area.y + bar_height {
+                    buf.get_mut(x, y).set_style(self.highlight_style);
+                }
+            }
+            // Height in eighths of a row, non-empty buckets always get a visible bar.
+            let mut eighths = (*count * bar_height as u64 * 8 / max).max(u64::from(*count > 0));
+            let mut y = area.y + bar_height;
+            while eighths > 0 && y > area.y {
+                y -= 1;
+                let symbol = BARS[(eighths.min(8) - 1) as usize];
+                buf.get_mut(x, y).set_symbol(symbol).set_style(style);
+                eighths = eighths.saturating_sub(8);
+            }
+        }
+
+        draw_caption(
+            buf,
+            model,
+            Rect::new(area.x, area.y + bar_height, area.width, 1),
+            self.caption_style,
+        );
+    }
+}
+
+fn draw_caption(buf: &mut Buffer, model: &HistogramModel, area: Rect, style: Style) {
+    match model.selected {
+        Some(i) => {
+            let (start, end) = model.bin_range(i);
+            let text = format!(
+                "{} - {}  {} events",
+                format_timestamp(start * 1000),
+                format_timestamp(end * 1000),
+                model.bins[i]
+            );
+            buf.set_stringn(
+                area.x,
+                area.y,
+                text,
+                area.width as usize,
+                style.add_modifier(Modifier::BOLD).fg(Color::White),
+            );
+        }
+        None => {
+            let (start, end) = match model.range {
+                Some(range) => range,
+                None => return,
+            };
+            let left = format_timestamp(start * 1000);
+            let right = format_timestamp(end * 1000);
+            buf.set_stringn(area.x, area.y, &left, area.width as usize, style);
+            if (left.len() + right.len() + 1) < area.width as usize {
+                let x = area.x + area.width - right.len() as u16;
+                buf.set_string(x, area.y, &right, style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_fit_the_width() {
+        let mut model = HistogramModel::new();
+        model.set_range(Some((1_633_046_407, 1_633_046_407 + 99)));
+        for width in [1, 2, 10, 19, 20, 21, 99, 100, 101] {
+            model.resize(width);
+            assert!(
+                model.bins.len() <= width as usize,
+                "{} buckets of {}s for a width of {}",
+                model.bins.len(),
+                model.bin_secs,
+                width
+            );
+            let (first, _) = model.bin_range(0);
+            let (_, last) = model.bin_range(model.bins.len() - 1);
+            assert!(first <= 1_633_046_407 && last >= 1_633_046_506);
+        }
+    }
+}
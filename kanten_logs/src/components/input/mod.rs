@@ -7,11 +7,13 @@ use tui::{
     Frame,
 };
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-
+use anyhow::Result;
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 use super::BlockComponent;
+use crate::keymap::Action;
+use crate::models::History;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Position {
@@ -25,6 +27,18 @@ impl Default for Position {
     }
 }
 
+/// Reverse search through the history, like Ctrl-R in a shell.
+#[derive(Debug, Clone, PartialEq)]
+struct HistorySearch {
+    query: String,
+    /// The matching entry, shown as the value.
+    index: Option<usize>,
+    /// Nothing older matches the query.
+    failing: bool,
+    /// The value before searching, restored when it is cancelled.
+    original: String,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct InputModel<'a> {
     style: Style,
@@ -34,6 +48,10 @@ pub struct InputModel<'a> {
     focused: bool,
     block: Option<Block<'a>>,
     focused_block: Option<Block<'a>>,
+    history: Option<History>,
+    search: Option<HistorySearch>,
+    /// Text removed by the last kill, put back with a yank.
+    kill_buffer: String,
 }
 
 impl<'a> InputModel<'a> {
@@ -46,6 +64,9 @@ impl<'a> InputModel<'a> {
             focused: false,
             block: None,
             focused_block: None,
+            history: None,
+            search: None,
+            kill_buffer: String::new(),
         }
     }
 
@@ -59,6 +80,12 @@ impl<'a> InputModel<'a> {
         self
     }
 
+    /// Replace the value and move the cursor to its end.
+    pub fn set_text(&mut self, v: impl Into<String>) {
+        self.value = v.into();
+        self.move_to(self.len());
+    }
+
     pub fn value(&self) -> &str {
         self.value.as_str()
     }
@@ -68,13 +95,6 @@ impl<'a> InputModel<'a> {
         self.value.is_empty()
     }
 
-    pub fn clamp_by(&self, w: usize) -> String {
-        UnicodeSegmentation::graphemes(self.value.as_str(), true)
-            .take(w)
-            .collect::<Vec<&str>>()
-            .join("")
-    }
-
     pub fn placeholder(&self) -> &str {
         self.placeholder.as_str()
     }
@@ -89,6 +109,36 @@ impl<'a> InputModel<'a> {
         self
     }
 
+    /// Recall past values, with Up/Down and Ctrl-R by default.
+    pub fn history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Remember the value in the history.
+    pub fn commit(&mut self) -> Result<()> {
+        self.search = None;
+        match self.history {
+            Some(ref mut history) => history.push(&self.value),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// `(failing) history search: query`, shown in front of the value while searching.
+    fn search_prompt(&self) -> Option<String> {
+        self.search.as_ref().map(|search| {
+            format!(
+                "({}history search: {}) ",
+                if search.failing { "failing " } else { "" },
+                search.query
+            )
+        })
+    }
+
     #[allow(dead_code)]
     pub fn style(mut self, style: Style) -> Self {
         self.style = style;
@@ -105,93 +155,245 @@ impl<'a> InputModel<'a> {
 
     pub fn blur(&mut self) {
         self.focused = false;
+        self.search = None;
     }
 
-    pub fn on_key(&mut self, key: KeyEvent) {
-        match key {
-            // start
-            KeyEvent {
-                code: KeyCode::Char('a'),
-                modifiers: KeyModifiers::CONTROL,
-            } => {
-                self.cursor_position.x = 0;
+    /// Returns false for actions that end the search, which then act as usual.
+    fn on_search_action(&mut self, action: Action) -> bool {
+        let search = match self.search.as_mut() {
+            Some(search) => search,
+            None => return false,
+        };
+        // From the newest entry, or from the shown one as long as it still matches.
+        let before = match action {
+            Action::HistorySearch => search.index.unwrap_or(usize::MAX),
+            Action::DeleteCharBackward => {
+                search.query.pop();
+                usize::MAX
             }
-            // end
-            KeyEvent {
-                code: KeyCode::Char('e'),
-                modifiers: KeyModifiers::CONTROL,
-            } => {
-                let size = UnicodeSegmentation::graphemes(self.value(), true).count() as u16;
-                self.cursor_position.x = size;
+            Action::Cancel => {
+                let original = search.original.clone();
+                self.search = None;
+                self.set_text(original);
+                return true;
             }
-            // ->
-            KeyEvent {
-                code: KeyCode::Char('f'),
-                modifiers: KeyModifiers::CONTROL,
+            _ => return false,
+        };
+        self.search_history(before);
+        true
+    }
+
+    fn search_history(&mut self, before: usize) {
+        let (history, mut search) = match (self.history.as_ref(), self.search.take()) {
+            (Some(history), Some(search)) => (history, search),
+            _ => return,
+        };
+        match history.find(&search.query, before) {
+            Some(index) => {
+                let value = history.get(index).unwrap_or_default().to_owned();
+                search.index = Some(index);
+                search.failing = false;
+                self.set_text(value);
+            }
+            None => search.failing = true,
+        }
+        self.search = Some(search);
+    }
+
+    /// Type a character, into the query while searching the history.
+    pub fn on_text(&mut self, c: char) {
+        match self.search {
+            Some(ref mut search) => {
+                search.query.push(c);
+                let before = search.index.map(|i| i + 1).unwrap_or(usize::MAX);
+                self.search_history(before);
             }
-            | KeyEvent {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::NONE,
-            } => {
-                let size = UnicodeSegmentation::graphemes(self.value(), true).count() as u16;
-                if self.cursor_position.x == size {
-                    return;
+            None => self.insert_str(c.encode_utf8(&mut [0; 4])),
+        }
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        if self.search.is_some() && self.on_search_action(action) {
+            return;
+        }
+        self.search = None;
+        match action {
+            Action::HistoryPrev => {
+                let value = match self.history {
+                    Some(ref mut history) => history.previous(&self.value).map(str::to_owned),
+                    None => None,
+                };
+                if let Some(value) = value {
+                    self.set_text(value);
                 }
-                self.cursor_position.x += 1;
             }
-            // <-
-            KeyEvent {
-                code: KeyCode::Char('b'),
-                modifiers: KeyModifiers::CONTROL,
+            Action::HistoryNext => {
+                let value = match self.history {
+                    Some(ref mut history) => history.next().map(str::to_owned),
+                    None => None,
+                };
+                if let Some(value) = value {
+                    self.set_text(value);
+                }
             }
-            | KeyEvent {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::NONE,
-            } => self.cursor_position.x = self.cursor_position.x.saturating_sub(1),
-            // input
-            KeyEvent {
-                code: KeyCode::Char(c),
-                modifiers: KeyModifiers::NONE,
+            Action::HistorySearch if self.history.is_some() => {
+                self.search = Some(HistorySearch {
+                    query: String::new(),
+                    index: None,
+                    failing: false,
+                    original: self.value.clone(),
+                });
             }
-            | KeyEvent {
-                code: KeyCode::Char(c),
-                modifiers: KeyModifiers::SHIFT,
-            } => {
-                self.value.insert(self.cursor_position.x as usize, c);
-                self.cursor_position.x += 1;
+            Action::LineStart => self.cursor_position.x = 0,
+            Action::LineEnd => self.move_to(self.len()),
+            Action::CharForward => self.move_to(self.cursor() + 1),
+            Action::CharBackward => self.move_to(self.cursor().saturating_sub(1)),
+            Action::WordForward => self.move_to(self.word_end()),
+            Action::WordBackward => self.move_to(self.word_start(is_word)),
+            Action::KillWordBackward => {
+                self.kill(self.word_start(|g| !is_whitespace(g)), self.cursor())
             }
-            // delete
-            KeyEvent {
-                code: KeyCode::Char('d'),
-                modifiers: KeyModifiers::CONTROL,
+            Action::KillWord => self.kill(self.cursor(), self.word_end()),
+            Action::KillToEnd => self.kill(self.cursor(), self.len()),
+            Action::KillToStart => self.kill(0, self.cursor()),
+            Action::Yank => {
+                let killed = self.kill_buffer.clone();
+                self.insert_str(&killed);
             }
-            | KeyEvent {
-                code: KeyCode::Delete,
-                modifiers: KeyModifiers::NONE,
-            } => {
-                if self.value.is_empty() {
-                    return;
+            Action::DeleteChar => {
+                let cursor = self.cursor();
+                if cursor < self.len() {
+                    self.remove(cursor, cursor + 1);
                 }
-                self.value.remove(self.cursor_position.x as usize);
             }
-            // backspace
-            KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Backspace,
-                modifiers: KeyModifiers::NONE,
-            } => {
-                if self.cursor_position.x == 0 {
-                    return;
+            Action::DeleteCharBackward => {
+                let cursor = self.cursor();
+                if cursor > 0 {
+                    self.remove(cursor - 1, cursor);
                 }
-                self.value.remove(self.cursor_position.x as usize - 1);
-                self.cursor_position.x -= 1;
             }
             _ => {}
         }
     }
+
+    /// Insert text at the cursor. Line breaks of pasted text become spaces and
+    /// other control characters are dropped, as the input is a single line.
+    pub fn insert_str(&mut self, text: &str) {
+        self.search = None;
+        let text: String = text
+            .replace("\r\n", "\n")
+            .chars()
+            .filter_map(|c| match c {
+                '\n' | '\r' | '\t' => Some(' '),
+                c if c.is_control() => None,
+                c => Some(c),
+            })
+            .collect();
+        let at = self.byte_index(self.cursor());
+        self.value.insert_str(at, &text);
+        self.set_cursor_at_byte(at + text.len());
+    }
+
+    /// Number of graphemes, which the cursor counts in.
+    fn len(&self) -> usize {
+        self.value.graphemes(true).count()
+    }
+
+    fn cursor(&self) -> usize {
+        (self.cursor_position.x as usize).min(self.len())
+    }
+
+    fn move_to(&mut self, index: usize) {
+        self.cursor_position.x = index.min(self.len()) as u16;
+    }
+
+    fn set_cursor_at_byte(&mut self, byte: usize) {
+        self.cursor_position.x = self.value[..byte].graphemes(true).count() as u16;
+    }
+
+    /// Byte offset of the grapheme at `index`, the length of the value past the last one.
+    fn byte_index(&self, index: usize) -> usize {
+        self.value
+            .grapheme_indices(true)
+            .nth(index)
+            .map(|(i, _)| i)
+            .unwrap_or_else(|| self.value.len())
+    }
+
+    /// Remove the graphemes from `start` to `end` and put the cursor at `start`.
+    fn remove(&mut self, start: usize, end: usize) -> String {
+        let (start, end) = (self.byte_index(start), self.byte_index(end));
+        let removed: String = self.value.drain(start..end).collect();
+        self.set_cursor_at_byte(start);
+        removed
+    }
+
+    /// Remove the graphemes from `start` to `end`, to be yanked back.
+    fn kill(&mut self, start: usize, end: usize) {
+        let killed = self.remove(start, end);
+        if !killed.is_empty() {
+            self.kill_buffer = killed;
+        }
+    }
+
+    /// Start of the word before the cursor, whose graphemes satisfy `in_word`.
+    fn word_start(&self, in_word: impl Fn(&str) -> bool) -> usize {
+        let graphemes: Vec<&str> = self.value.graphemes(true).collect();
+        let mut i = self.cursor();
+        while i > 0 && !in_word(graphemes[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && in_word(graphemes[i - 1]) {
+            i -= 1;
+        }
+        i
+    }
+
+    /// End of the word after the cursor.
+    fn word_end(&self) -> usize {
+        let graphemes: Vec<&str> = self.value.graphemes(true).collect();
+        let mut i = self.cursor();
+        while i < graphemes.len() && !is_word(graphemes[i]) {
+            i += 1;
+        }
+        while i < graphemes.len() && is_word(graphemes[i]) {
+            i += 1;
+        }
+        i
+    }
+
+    /// The part of the value that fits in `width` columns, scrolled so that the
+    /// cursor stays visible, and the column of the cursor in it.
+    fn visible(&self, width: usize) -> (String, u16) {
+        let graphemes: Vec<(&str, usize)> =
+            self.value.graphemes(true).map(|g| (g, g.width())).collect();
+        let cursor = self.cursor();
+        let mut start = 0;
+        let mut column: usize = graphemes[..cursor].iter().map(|(_, w)| w).sum();
+        // The cursor takes a column after the text before it.
+        while start < cursor && column + 1 > width {
+            column -= graphemes[start].1;
+            start += 1;
+        }
+        let mut text = String::new();
+        let mut used = 0;
+        for (g, w) in graphemes[start..].iter() {
+            if used + w > width {
+                break;
+            }
+            text.push_str(g);
+            used += w;
+        }
+        (text, column as u16)
+    }
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().next().map(char::is_alphanumeric) == Some(true)
+}
+
+fn is_whitespace(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -207,15 +409,18 @@ impl<'a> InputView<'a> {
 
 impl<'a> BlockComponent for InputView<'a> {
     fn draw<B: Backend>(self, f: &mut Frame<B>, area: Rect) {
-        let cursor_position = self.model.cursor_position;
         let focused = self.model.focused;
         let inner_area = match self.model.block.as_ref() {
             Some(b) => b.inner(area),
             None => area,
         };
+        let column = match self.model.search_prompt() {
+            Some(prompt) => prompt.width() as u16 - 2,
+            None => self.model.visible(inner_area.width as usize).1,
+        };
         f.render_widget(self, area);
-        if focused && (inner_area.left() + cursor_position.x) < inner_area.right() {
-            f.set_cursor(inner_area.left() + cursor_position.x, inner_area.top());
+        if focused && (inner_area.left() + column) < inner_area.right() {
+            f.set_cursor(inner_area.left() + column, inner_area.top());
         }
     }
 }
@@ -250,7 +455,22 @@ impl<'a> Widget for InputView<'a> {
             (area.top(), area.height)
         };
 
-        if self.model.value().is_empty() {
+        if let Some(prompt) = self.model.search_prompt() {
+            let (x, _) = buf.set_stringn(
+                area.left(),
+                top,
+                &prompt,
+                area.width as usize,
+                Style::default().fg(Color::DarkGray),
+            );
+            buf.set_stringn(
+                x,
+                top,
+                self.model.value(),
+                area.right().saturating_sub(x) as usize,
+                Style::default(),
+            );
+        } else if self.model.value().is_empty() {
             buf.set_string(
                 area.left(),
                 top,
@@ -261,9 +481,118 @@ impl<'a> Widget for InputView<'a> {
             buf.set_string(
                 area.left(),
                 top,
-                self.model.clamp_by((area.right() - area.left()) as usize),
+                self.model.visible(area.width as usize).0,
                 Style::default(),
             );
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "é" as an `e` followed by a combining acute accent, a single grapheme.
+    const E_ACUTE: &str = "e\u{301}";
+
+    fn input(value: &str) -> InputModel<'static> {
+        let mut input = InputModel::new();
+        input.set_text(value);
+        input
+    }
+
+    fn apply(input: &mut InputModel, actions: &[Action]) {
+        for action in actions {
+            input.on_action(*action);
+        }
+    }
+
+    #[test]
+    fn inserts_text_at_the_cursor() {
+        let mut input = input("ログ");
+        input.on_action(Action::CharBackward);
+        input.insert_str(E_ACUTE);
+        assert_eq!(input.value(), "ロe\u{301}グ");
+        assert_eq!(input.cursor(), 2);
+
+        input.insert_str("a\r\nb\tc\u{7}");
+        assert_eq!(input.value(), "ロe\u{301}a b cグ");
+        assert_eq!(input.cursor(), 7);
+    }
+
+    #[test]
+    fn deletes_whole_graphemes() {
+        let mut input = input(&format!("c{}fé", E_ACUTE));
+        apply(&mut input, &[Action::CharBackward, Action::CharBackward]);
+        input.on_action(Action::DeleteCharBackward);
+        assert_eq!(input.value(), "cfé");
+        assert_eq!(input.cursor(), 1);
+
+        input.on_action(Action::DeleteChar);
+        assert_eq!(input.value(), "cé");
+        assert_eq!(input.remove(1, 2), "é");
+        assert_eq!(input.value(), "c");
+    }
+
+    #[test]
+    fn kills_and_yanks_words() {
+        let mut input = input("ログ 検索 kanten");
+        input.on_action(Action::KillWordBackward);
+        assert_eq!(input.value(), "ログ 検索 ");
+        input.on_action(Action::KillWordBackward);
+        assert_eq!(input.value(), "ログ ");
+        assert_eq!(input.kill_buffer, "検索 ");
+
+        input.on_action(Action::Yank);
+        assert_eq!(input.value(), "ログ 検索 ");
+        assert_eq!(input.cursor(), 6);
+
+        apply(&mut input, &[Action::LineStart, Action::KillWord]);
+        assert_eq!(input.value(), " 検索 ");
+        apply(&mut input, &[Action::CharForward, Action::KillToEnd]);
+        assert_eq!(input.value(), " ");
+        input.on_action(Action::KillToStart);
+        assert_eq!(input.value(), "");
+        assert_eq!(input.kill_buffer, " ");
+
+        // A kill of nothing keeps the text to yank.
+        input.on_action(Action::KillToEnd);
+        input.on_action(Action::Yank);
+        assert_eq!(input.value(), " ");
+    }
+
+    #[test]
+    fn moves_by_words() {
+        let mut input = input(&format!("ログ, 検索 {}tude", E_ACUTE));
+        input.on_action(Action::LineStart);
+        let mut ends = vec![];
+        for _ in 0..4 {
+            input.on_action(Action::WordForward);
+            ends.push(input.cursor());
+        }
+        assert_eq!(ends, vec![2, 6, 12, 12]);
+        assert_eq!(input.word_end(), 12);
+
+        let mut starts = vec![];
+        for _ in 0..4 {
+            input.on_action(Action::WordBackward);
+            starts.push(input.cursor());
+        }
+        assert_eq!(starts, vec![7, 4, 0, 0]);
+        assert_eq!(input.word_start(is_word), 0);
+    }
+
+    #[test]
+    fn scrolls_wide_text_to_the_cursor() {
+        let mut input = input("ログ検索");
+        assert_eq!(input.visible(5), ("検索".to_owned(), 4));
+        assert_eq!(input.visible(20), ("ログ検索".to_owned(), 8));
+
+        input.on_action(Action::LineStart);
+        assert_eq!(input.visible(5), ("ログ".to_owned(), 0));
+
+        let input = self::input(&E_ACUTE.repeat(3));
+        assert_eq!(input.visible(2), (E_ACUTE.to_owned(), 1));
+        assert_eq!(input.visible(10), (E_ACUTE.repeat(3), 3));
+    }
+}
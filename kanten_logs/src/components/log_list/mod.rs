@@ -7,15 +7,16 @@ use tui::{
     widgets::{Block, StatefulWidget, Widget},
 };
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-
 use crate::app::{Dispatcher, Message};
+use crate::keymap::Action;
 
 use self::line_builder::LineBuilder;
 
 pub struct LogListModel<D: Dispatcher<Message = Message>> {
     pub state: LogListState<D>,
     pub items: Vec<LogListItem>,
+    tailing: bool,
+    follow: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +64,8 @@ impl<D: Dispatcher<Message = Message>> LogListModel<D> {
         LogListModel {
             state,
             items: Vec::new(),
+            tailing: false,
+            follow: false,
         }
     }
 
@@ -72,6 +75,39 @@ impl<D: Dispatcher<Message = Message>> LogListModel<D> {
 
     pub fn push(&mut self, item: LogListItem) {
         self.items.push(item);
+        if self.tailing && self.follow {
+            self.state.select(Some(self.items.len() - 1));
+        }
+    }
+
+    /// Insert an item, keeping the same item selected if it moves down.
+    pub fn insert(&mut self, index: usize, item: LogListItem) {
+        let had_items = !self.items.is_empty();
+        self.items.insert(index, item);
+        if let Some(selected) = self.state.selected() {
+            if had_items && selected >= index {
+                self.state.select(Some(selected + 1));
+            }
+        }
+    }
+
+    /// While tailing, the newest (last) item stays selected as items are pushed
+    /// unless the selection was moved away from it.
+    pub fn set_tailing(&mut self, tailing: bool) {
+        self.tailing = tailing;
+        self.follow = tailing;
+    }
+
+    pub fn is_tailing(&self) -> bool {
+        self.tailing
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.tailing && self.follow
+    }
+
+    fn is_last_selected(&self) -> bool {
+        self.items.is_empty() || self.state.selected() == Some(self.items.len() - 1)
     }
 
     pub fn clear(&mut self) {
@@ -127,46 +163,18 @@ impl<D: Dispatcher<Message = Message>> LogListModel<D> {
         self.state.start_index = index;
     }
 
-    pub fn on_key(&mut self, key: KeyEvent) {
-        match key {
-            // down
-            KeyEvent {
-                code: KeyCode::Char('n'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Down,
-                modifiers: KeyModifiers::NONE,
-            } => self.next_if_exist(),
-            // up
-            KeyEvent {
-                code: KeyCode::Char('p'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::Up,
-                modifiers: KeyModifiers::NONE,
-            } => self.previous_if_exist(),
-            // page up
-            KeyEvent {
-                code: KeyCode::Char('v'),
-                modifiers: KeyModifiers::ALT,
-            }
-            | KeyEvent {
-                code: KeyCode::PageUp,
-                modifiers: KeyModifiers::NONE,
-            } => self.previous_page_if_exist(),
-            // page down
-            KeyEvent {
-                code: KeyCode::Char('v'),
-                modifiers: KeyModifiers::CONTROL,
-            }
-            | KeyEvent {
-                code: KeyCode::PageDown,
-                modifiers: KeyModifiers::NONE,
-            } => self.next_page_if_exist(),
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::Down => self.next_if_exist(),
+            Action::Up => self.previous_if_exist(),
+            Action::PageUp => self.previous_page_if_exist(),
+            Action::PageDown => self.next_page_if_exist(),
             _ => {}
         }
+        if self.tailing {
+            // Moving away from the newest item pauses following, coming back resumes it.
+            self.follow = self.is_last_selected();
+        }
     }
 }
 
@@ -174,6 +182,7 @@ impl<D: Dispatcher<Message = Message>> LogListModel<D> {
 pub struct LogListItem {
     log: String,
     timestamp: String,
+    fields: Vec<(String, String)>,
     style: Style,
     line_builder: LineBuilder,
 }
@@ -183,11 +192,48 @@ impl LogListItem {
         LogListItem {
             log,
             timestamp,
+            fields: vec![],
             style: Style::default(),
             line_builder: LineBuilder::new(),
         }
     }
 
+    pub fn set_fields(mut self, fields: Vec<(String, String)>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    pub fn message(&self) -> &str {
+        &self.log
+    }
+
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// All fields, or just the timestamp and message when the item has no fields.
+    pub fn fields(&self) -> Vec<(String, String)> {
+        if self.fields.is_empty() {
+            vec![
+                ("@timestamp".to_owned(), self.timestamp.clone()),
+                ("@message".to_owned(), self.log.clone()),
+            ]
+        } else {
+            self.fields.clone()
+        }
+    }
+
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(n, _)| n.as_str())
+    }
+
     // pub fn style(mut self, style: Style) -> Self {
     //     self.style = style;
     //     self
@@ -197,6 +243,15 @@ impl LogListItem {
         format!("{}    {}", self.timestamp, self.log)
     }
 
+    /// Whether the line contains the find text, case-insensitively like its highlighting.
+    pub fn contains(&self, find_text: &str) -> bool {
+        find_text.is_empty()
+            || self
+                .text()
+                .to_lowercase()
+                .contains(&find_text.to_lowercase())
+    }
+
     pub fn height(&self, w: u16) -> usize {
         self.line_builder.run_composer(&self.text(), w, "").len()
     }
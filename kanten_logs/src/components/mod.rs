@@ -1,13 +1,29 @@
 pub mod block_component;
 pub mod checkbox;
+pub mod context_view;
+pub mod detail_view;
 pub mod group_list;
+pub mod help_view;
+pub mod histogram;
 pub mod inline_component;
 pub mod input;
 pub mod log_list;
+pub mod preset_list;
+pub mod result_table;
+pub mod stats_view;
+pub mod stream_list;
 
 pub use block_component::*;
 pub use checkbox::*;
+pub use context_view::*;
+pub use detail_view::*;
 pub use group_list::*;
+pub use help_view::*;
+pub use histogram::*;
 pub use inline_component::*;
 pub use input::*;
 pub use log_list::*;
+pub use preset_list::*;
+pub use result_table::*;
+pub use stats_view::*;
+pub use stream_list::*;
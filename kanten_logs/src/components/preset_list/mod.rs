@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use tui::widgets::ListState;
+
+use crate::client::QueryDefinition;
+use crate::config::Preset;
+use crate::keymap::Action;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PresetSource {
+    /// The config file or `presets.toml`.
+    Local,
+    /// A saved query definition of CloudWatch Logs Insights.
+    CloudWatch,
+}
+
+#[derive(Debug, Clone)]
+pub struct PresetListItem {
+    pub name: String,
+    pub source: PresetSource,
+    pub preset: Preset,
+}
+
+/// Local presets followed by the CloudWatch query definitions, to pick one to load.
+pub struct PresetList {
+    pub state: ListState,
+    pub items: Vec<PresetListItem>,
+    /// Query definitions are being fetched.
+    pub loading: bool,
+}
+
+impl PresetList {
+    pub fn new() -> Self {
+        Self {
+            state: ListState::default(),
+            items: vec![],
+            loading: false,
+        }
+    }
+
+    /// List the local presets, the query definitions are added once fetched.
+    pub fn open(&mut self, presets: &BTreeMap<String, Preset>) {
+        self.items = presets
+            .iter()
+            .map(|(name, preset)| PresetListItem {
+                name: name.clone(),
+                source: PresetSource::Local,
+                preset: preset.clone(),
+            })
+            .collect();
+        self.state
+            .select(if self.items.is_empty() { None } else { Some(0) });
+        self.loading = true;
+    }
+
+    pub fn set_definitions(&mut self, definitions: Vec<QueryDefinition>) {
+        self.items
+            .retain(|item| item.source != PresetSource::CloudWatch);
+        self.items
+            .extend(definitions.iter().map(|definition| PresetListItem {
+                name: definition.name.clone(),
+                source: PresetSource::CloudWatch,
+                preset: Preset::from_definition(definition),
+            }));
+        if self.state.selected().is_none() && !self.items.is_empty() {
+            self.state.select(Some(0));
+        }
+        self.loading = false;
+    }
+
+    pub fn selected(&self) -> Option<&PresetListItem> {
+        self.state.selected().and_then(|i| self.items.get(i))
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::Down => self.next(),
+            Action::Up => self.previous(),
+            _ => {}
+        }
+    }
+
+    pub fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.items.len() => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(0) | None => self.items.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+}
@@ -0,0 +1,213 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Cell, Row, Table, TableState},
+    Frame,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::client::DEFAULT_FIELDS;
+use crate::keymap::Action;
+
+use super::{BlockComponent, LogListItem};
+
+const MAX_COLUMN_WIDTH: u16 = 40;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub width: u16,
+    pub hidden: bool,
+}
+
+/// Columns and selection for showing query results with arbitrary fields as a table.
+#[derive(Debug, Clone)]
+pub struct ResultTableModel {
+    pub columns: Vec<Column>,
+    pub state: TableState,
+    /// Index of the selected column among the visible ones.
+    pub column_index: usize,
+}
+
+impl ResultTableModel {
+    pub fn new() -> Self {
+        let mut state = TableState::default();
+        state.select(Some(0));
+        Self {
+            columns: vec![],
+            state,
+            column_index: 0,
+        }
+    }
+
+    /// True when the results have fields besides the ones of the default query,
+    /// e.g. from `parse`, `stats` or `display`.
+    pub fn is_tabular(&self) -> bool {
+        self.columns
+            .iter()
+            .any(|c| !DEFAULT_FIELDS.contains(&c.name.as_str()))
+    }
+
+    /// Add a column for every field not seen yet and widen columns to fit their values.
+    pub fn update_columns(&mut self, items: &[LogListItem]) {
+        for item in items {
+            for name in item.field_names() {
+                if name == "@ptr" {
+                    continue;
+                }
+                let width = (item.field(name).unwrap_or_default().width() as u16)
+                    .max(name.width() as u16)
+                    .min(MAX_COLUMN_WIDTH);
+                match self.columns.iter_mut().find(|c| c.name == name) {
+                    Some(c) => c.width = c.width.max(width),
+                    None => self.columns.push(Column {
+                        name: name.to_owned(),
+                        width,
+                        hidden: false,
+                    }),
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.columns = vec![];
+        self.column_index = 0;
+        self.state.select(Some(0));
+    }
+
+    pub fn visible_columns(&self) -> impl Iterator<Item = &Column> {
+        self.columns.iter().filter(|c| !c.hidden)
+    }
+
+    fn selected_column_mut(&mut self) -> Option<&mut Column> {
+        let index = self.column_index;
+        self.columns.iter_mut().filter(|c| !c.hidden).nth(index)
+    }
+
+    pub fn next_column(&mut self) {
+        if self.column_index + 1 < self.visible_columns().count() {
+            self.column_index += 1;
+        }
+    }
+
+    /// Hide the selected column, keeping at least one visible.
+    pub fn hide_column(&mut self) {
+        if self.visible_columns().count() <= 1 {
+            return;
+        }
+        if let Some(c) = self.selected_column_mut() {
+            c.hidden = true;
+        }
+        let count = self.visible_columns().count();
+        self.column_index = self.column_index.min(count - 1);
+    }
+
+    pub fn on_action(&mut self, action: Action, len: usize) {
+        match action {
+            Action::Down => {
+                let i = self.state.selected().unwrap_or(0);
+                if i + 1 < len {
+                    self.state.select(Some(i + 1));
+                }
+            }
+            Action::Up => {
+                let i = self.state.selected().unwrap_or(0);
+                self.state.select(Some(i.saturating_sub(1)));
+            }
+            Action::Right => self.next_column(),
+            Action::Left => self.column_index = self.column_index.saturating_sub(1),
+            Action::WidenColumn => {
+                if let Some(c) = self.selected_column_mut() {
+                    c.width = c.width.saturating_add(2);
+                }
+            }
+            Action::NarrowColumn => {
+                if let Some(c) = self.selected_column_mut() {
+                    c.width = c.width.saturating_sub(2).max(1);
+                }
+            }
+            Action::HideColumn => self.hide_column(),
+            Action::ShowColumns => {
+                for c in self.columns.iter_mut() {
+                    c.hidden = false;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct ResultTable<'a> {
+    model: &'a mut ResultTableModel,
+    items: &'a [LogListItem],
+    block: Option<Block<'a>>,
+    highlight_style: Style,
+    header_style: Style,
+}
+
+impl<'a> ResultTable<'a> {
+    pub fn new(model: &'a mut ResultTableModel, items: &'a [LogListItem]) -> Self {
+        Self {
+            model,
+            items,
+            block: None,
+            highlight_style: Style::default(),
+            header_style: Style::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    /// Header of the selected column.
+    pub fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+}
+
+impl<'a> BlockComponent for ResultTable<'a> {
+    fn draw<B: Backend>(self, f: &mut Frame<B>, area: Rect) {
+        let columns: Vec<Column> = self.model.visible_columns().cloned().collect();
+        let widths: Vec<Constraint> = columns
+            .iter()
+            .map(|c| Constraint::Length(c.width))
+            .collect();
+
+        let header = Row::new(columns.iter().enumerate().map(|(i, c)| {
+            let style = if i == self.model.column_index {
+                self.header_style
+            } else {
+                Style::default().add_modifier(Modifier::BOLD)
+            };
+            Cell::from(c.name.clone()).style(style)
+        }));
+
+        let rows =
+            self.items.iter().map(|item| {
+                Row::new(columns.iter().map(|c| {
+                    Cell::from(item.field(&c.name).unwrap_or_default().replace('\n', " "))
+                }))
+            });
+
+        let mut table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .column_spacing(1)
+            .highlight_style(self.highlight_style);
+        if let Some(block) = self.block {
+            table = table.block(block);
+        }
+        f.render_stateful_widget(table, area, &mut self.model.state);
+    }
+}
@@ -0,0 +1,352 @@
+use std::cmp::Ordering;
+
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{BarChart, Block, Borders, Cell, Row, Sparkline, Table, TableState},
+    Frame,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::client::SearchResultItem;
+use crate::keymap::Action;
+
+use super::BlockComponent;
+
+const CHART_HEIGHT: u16 = 10;
+
+/// Aggregated rows returned by a `stats` query.
+#[derive(Debug, Clone)]
+pub struct StatsModel {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Column the rows are sorted by, and whether in descending order.
+    pub sort: Option<(usize, bool)>,
+    pub state: TableState,
+    pub column_index: usize,
+}
+
+impl StatsModel {
+    pub fn new() -> Self {
+        let mut state = TableState::default();
+        state.select(Some(0));
+        Self {
+            columns: vec![],
+            rows: vec![],
+            sort: None,
+            state,
+            column_index: 0,
+        }
+    }
+
+    /// Stats results are aggregates, so Logs Insights returns them without `@ptr`.
+    pub fn is_aggregate(items: &[SearchResultItem]) -> bool {
+        !items.is_empty() && items.iter().all(|item| item.get("@ptr").is_none())
+    }
+
+    /// Replace the rows. While the columns stay the same, as with each poll of a
+    /// running query, the sort, the selected column and the selected row are kept.
+    pub fn set_items(&mut self, items: &[SearchResultItem]) {
+        let mut columns: Vec<String> = vec![];
+        for item in items {
+            for (name, _) in item.fields.iter() {
+                if !columns.contains(name) {
+                    columns.push(name.clone());
+                }
+            }
+        }
+        self.rows = items
+            .iter()
+            .map(|item| {
+                columns
+                    .iter()
+                    .map(|c| item.get(c).unwrap_or_default().to_owned())
+                    .collect()
+            })
+            .collect();
+        if columns != self.columns {
+            self.columns = columns;
+            self.sort = None;
+            self.state.select(Some(0));
+            self.column_index = 0;
+            return;
+        }
+        if let Some((column, descending)) = self.sort {
+            self.sort_rows(column, descending);
+        }
+        let selected = self.state.selected().unwrap_or(0);
+        self.state
+            .select(Some(selected.min(self.rows.len().saturating_sub(1))));
+    }
+
+    pub fn clear(&mut self) {
+        self.columns = vec![];
+        self.rows = vec![];
+        self.sort = None;
+        self.state.select(Some(0));
+        self.column_index = 0;
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.columns.is_empty()
+    }
+
+    /// The `bin(...)` column and the first numeric column, if the rows are a time series.
+    pub fn time_series(&self) -> Option<(usize, usize)> {
+        let label = self.columns.iter().position(|c| c.starts_with("bin("))?;
+        let value = (0..self.columns.len())
+            .find(|i| *i != label && self.rows.iter().all(|r| r[*i].parse::<f64>().is_ok()))?;
+        Some((label, value))
+    }
+
+    /// Values of the time series in chronological order.
+    pub fn series(&self) -> Vec<(String, u64)> {
+        let (label, value) = match self.time_series() {
+            Some(s) => s,
+            None => return vec![],
+        };
+        let mut series: Vec<(String, u64)> = self
+            .rows
+            .iter()
+            .map(|r| {
+                let v = r[value].parse::<f64>().unwrap_or_default();
+                (r[label].clone(), v.max(0.0).round() as u64)
+            })
+            .collect();
+        series.sort_by(|a, b| a.0.cmp(&b.0));
+        series
+    }
+
+    pub fn sort_by_selected_column(&mut self) {
+        let column = self.column_index;
+        let descending = match self.sort {
+            Some((c, descending)) if c == column => !descending,
+            _ => true,
+        };
+        self.sort_rows(column, descending);
+        self.sort = Some((column, descending));
+    }
+
+    fn sort_rows(&mut self, column: usize, descending: bool) {
+        self.rows.sort_by(|a, b| {
+            let ord = compare(&a[column], &b[column]);
+            if descending {
+                ord.reverse()
+            } else {
+                ord
+            }
+        });
+    }
+
+    pub fn on_action(&mut self, action: Action) {
+        match action {
+            Action::Down => {
+                let i = self.state.selected().unwrap_or(0);
+                if i + 1 < self.rows.len() {
+                    self.state.select(Some(i + 1));
+                }
+            }
+            Action::Up => {
+                let i = self.state.selected().unwrap_or(0);
+                self.state.select(Some(i.saturating_sub(1)));
+            }
+            Action::Right => {
+                self.column_index = (self.column_index + 1).min(self.columns.len() - 1);
+            }
+            Action::Left => self.column_index = self.column_index.saturating_sub(1),
+            Action::Sort => self.sort_by_selected_column(),
+            _ => {}
+        }
+    }
+}
+
+/// Compare numerically when both values are numbers.
+fn compare(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+pub struct StatsView<'a> {
+    model: &'a mut StatsModel,
+    block: Option<Block<'a>>,
+    highlight_style: Style,
+    header_style: Style,
+}
+
+impl<'a> StatsView<'a> {
+    pub fn new(model: &'a mut StatsModel) -> Self {
+        Self {
+            model,
+            block: None,
+            highlight_style: Style::default(),
+            header_style: Style::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    /// Header of the selected column.
+    pub fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+}
+
+impl<'a> BlockComponent for StatsView<'a> {
+    fn draw<B: Backend>(self, f: &mut Frame<B>, area: Rect) {
+        let area = match self.block {
+            Some(b) => {
+                let inner_area = b.inner(area);
+                f.render_widget(b, area);
+                inner_area
+            }
+            None => area,
+        };
+
+        let series = self.model.series();
+        let table_area = if series.is_empty() || area.height <= CHART_HEIGHT + 3 {
+            area
+        } else {
+            let chunks = Layout::default()
+                .constraints([Constraint::Length(CHART_HEIGHT), Constraint::Min(3)].as_ref())
+                .direction(Direction::Vertical)
+                .split(area);
+            draw_chart(f, &series, chunks[0]);
+            chunks[1]
+        };
+
+        let header_style = self.header_style;
+        let model = self.model;
+        let widths: Vec<Constraint> = model
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let w = model
+                    .rows
+                    .iter()
+                    .map(|r| r[i].width())
+                    .chain(std::iter::once(c.width() + 2))
+                    .max()
+                    .unwrap_or_default();
+                Constraint::Length(w as u16)
+            })
+            .collect();
+
+        let header = Row::new(model.columns.iter().enumerate().map(|(i, c)| {
+            let name = match model.sort {
+                Some((s, true)) if s == i => format!("{} ▼", c),
+                Some((s, false)) if s == i => format!("{} ▲", c),
+                _ => c.clone(),
+            };
+            let style = if i == model.column_index {
+                header_style
+            } else {
+                Style::default().add_modifier(Modifier::BOLD)
+            };
+            Cell::from(name).style(style)
+        }));
+        let rows = model
+            .rows
+            .iter()
+            .map(|r| Row::new(r.iter().map(|v| Cell::from(v.as_str()))));
+
+        let table = Table::new(rows)
+            .header(header)
+            .widths(&widths)
+            .column_spacing(2)
+            .highlight_style(self.highlight_style);
+        f.render_stateful_widget(table, table_area, &mut model.state);
+    }
+}
+
+fn draw_chart<B: Backend>(f: &mut Frame<B>, series: &[(String, u64)], area: Rect) {
+    let block = Block::default().borders(Borders::BOTTOM);
+    let width = block.inner(area).width as usize;
+
+    // Too many bins for labelled bars, so show the shape of the series instead.
+    if series.len() * 2 > width {
+        let data: Vec<u64> = series.iter().map(|(_, v)| *v).collect();
+        let sparkline = Sparkline::default()
+            .block(block)
+            .data(&data)
+            .style(Style::default().fg(Color::Rgb(238, 173, 15)));
+        f.render_widget(sparkline, area);
+        return;
+    }
+
+    // Bin labels are timestamps like `2021-10-01 12:05:00.000`, keep the time of day.
+    let labels: Vec<String> = series
+        .iter()
+        .map(|(l, _)| l.get(11..16).unwrap_or(l).to_owned())
+        .collect();
+    let data: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(series.iter())
+        .map(|(l, (_, v))| (l.as_str(), *v))
+        .collect();
+    let bar_width = ((width / series.len()).saturating_sub(1)).clamp(1, 8) as u16;
+    let chart = BarChart::default()
+        .block(block)
+        .data(&data)
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Rgb(238, 173, 15)))
+        .value_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Rgb(238, 173, 15)),
+        );
+    f.render_widget(chart, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(counts: &[(&str, &str)]) -> Vec<SearchResultItem> {
+        counts
+            .iter()
+            .map(|(level, count)| SearchResultItem {
+                fields: vec![
+                    ("level".to_owned(), level.to_string()),
+                    ("count(*)".to_owned(), count.to_string()),
+                ],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_sort_and_selection_while_the_columns_stay() {
+        let mut stats = StatsModel::new();
+        stats.set_items(&items(&[("info", "1"), ("error", "3"), ("warn", "2")]));
+        stats.on_action(Action::Right);
+        stats.sort_by_selected_column();
+        stats.state.select(Some(2));
+
+        stats.set_items(&items(&[("info", "4"), ("error", "3")]));
+        assert_eq!(stats.sort, Some((1, true)));
+        assert_eq!(stats.column_index, 1);
+        assert_eq!(stats.state.selected(), Some(1));
+        assert_eq!(stats.rows[0], vec!["info", "4"]);
+
+        stats.set_items(&[SearchResultItem {
+            fields: vec![("count(*)".to_owned(), "7".to_owned())],
+        }]);
+        assert_eq!(stats.sort, None);
+        assert_eq!(stats.column_index, 0);
+        assert_eq!(stats.state.selected(), Some(0));
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time::{Duration, Instant},
+};
+use tui::widgets::ListState;
+
+use crate::client::LogStream;
+use crate::keymap::Action;
+
+/// How long a group has to stay highlighted before its streams are fetched, so
+/// that moving through the groups doesn't send a request for each of them.
+const LOAD_DELAY: Duration = Duration::from_millis(300);
+
+/// Streams of the highlighted log group, and the streams selected in each group.
+pub struct StreamList {
+    pub state: ListState,
+    /// The group whose streams are listed.
+    pub group: Option<String>,
+    pub items: Vec<LogStream>,
+    pub loading: bool,
+    pub selected: BTreeMap<String, BTreeSet<String>>,
+    load_at: Option<Instant>,
+}
+
+impl StreamList {
+    pub fn new() -> Self {
+        Self {
+            state: ListState::default(),
+            group: None,
+            items: vec![],
+            loading: false,
+            selected: BTreeMap::new(),
+            load_at: None,
+        }
+    }
+
+    pub fn set_group(&mut self, group: Option<String>) {
+        if self.group == group {
+            return;
+        }
+        self.group = group;
+        self.items.clear();
+        self.state.select(None);
+        self.loading = self.group.is_some();
+        self.load_at = self.group.as_ref().map(|_| Instant::now() + LOAD_DELAY);
+    }
+
+    /// Returns the group once it has been highlighted long enough to fetch its streams.
+    pub fn take_due(&mut self) -> Option<String> {
+        if self.load_at? > Instant::now() {
+            return None;
+        }
+        self.load_at = None;
+        self.group.clone()
+    }
+
+    /// Fetch the streams of the group again, e.g. after the duration changed.
+    pub fn reload(&mut self) {
+        self.group = None;
+    }
+
+    /// Ignores streams of a group that is no longer highlighted.
+    pub fn set_items(&mut self, group: &str, items: Vec<LogStream>) {
+        if self.group.as_deref() != Some(group) {
+            return;
+        }
+        self.state
+            .select(if items.is_empty() { None } else { Some(0) });
+        self.items = items;
+        self.loading = false;
+    }
+
+    pub fn fail(&mut self, group: &str) {
+        if self.group.as_deref() == Some(group) {
+            self.loading = false;
+        }
+    }
+
+    pub fn is_selected(&self, stream: &str) -> bool {
+        self.group
+            .as_ref()
+            .and_then(|group| self.selected.get(group))
+            .map(|streams| streams.contains(stream))
+            .unwrap_or(false)
+    }
+
+    /// Selected streams of `groups`, leaving out groups without any.
+    pub fn stream_names(&self, groups: &[String]) -> HashMap<String, Vec<String>> {
+        groups
+            .iter()
+            .filter_map(|group| {
+                self.selected
+                    .get(group)
+                    .map(|streams| (group.clone(), streams.iter().cloned().collect()))
+            })
+            .collect()
+    }
+
+    /// Returns whether the selection changed.
+    pub fn on_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Select => {
+                let (group, stream) = match (
+                    self.group.clone(),
+                    self.state.selected().and_then(|i| self.items.get(i)),
+                ) {
+                    (Some(group), Some(stream)) => (group, stream.name.clone()),
+                    _ => return false,
+                };
+                let streams = self.selected.entry(group.clone()).or_default();
+                if !streams.remove(&stream) {
+                    streams.insert(stream);
+                }
+                if streams.is_empty() {
+                    self.selected.remove(&group);
+                }
+                return true;
+            }
+            Action::Down => self.next(),
+            Action::Up => self.previous(),
+            _ => {}
+        }
+        false
+    }
+
+    pub fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.items.len() => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(0) | None => self.items.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+}
@@ -0,0 +1,190 @@
+mod theme;
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::client::{default_query, default_query_filter, QueryDefinition};
+use crate::keymap::Keymap;
+use crate::models::SearchMode;
+
+pub use theme::Theme;
+
+/// Presets saved from the TUI, next to the config file so that saving doesn't rewrite it.
+const SAVED_PRESETS_FILE: &str = "presets.toml";
+
+/// Search settings, either the defaults at the top of the config file or a named preset.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preset {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    /// Log group name (regular expression).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    /// Log group names, used when there is no `group_name`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// "insights", "query" or "filter".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_prefix: Option<String>,
+}
+
+impl Preset {
+    /// `group_name`, or a regular expression matching exactly `groups`.
+    pub fn group_pattern(&self) -> Option<String> {
+        if self.group_name.is_some() || self.groups.is_empty() {
+            return self.group_name.clone();
+        }
+        let groups: Vec<String> = self.groups.iter().map(|g| regex::escape(g)).collect();
+        Some(format!("^({})$", groups.join("|")))
+    }
+
+    /// A CloudWatch query definition as a preset, in insights mode when kanten saved it.
+    pub fn from_definition(definition: &QueryDefinition) -> Self {
+        let (mode, filter) = match default_query_filter(&definition.query) {
+            Some(filter) => (SearchMode::Insights, filter),
+            None => (SearchMode::Query, definition.query.as_str()),
+        };
+        Self {
+            groups: definition.groups.clone(),
+            filter: Some(filter.to_owned()),
+            mode: Some(mode.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// The preset as a CloudWatch query definition, `None` in filter mode which has no query.
+    /// The duration isn't part of a definition.
+    pub fn to_definition(&self, name: &str) -> Option<QueryDefinition> {
+        let filter = self.filter.as_deref().unwrap_or_default();
+        let query = match self.mode.as_deref().map(str::parse) {
+            None | Some(Ok(SearchMode::Insights)) => default_query(filter),
+            Some(Ok(SearchMode::Query)) => filter.to_owned(),
+            Some(Ok(SearchMode::Filter)) | Some(Err(_)) => return None,
+        };
+        Some(QueryDefinition {
+            id: None,
+            name: name.to_owned(),
+            query,
+            groups: self.groups.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SavedPresets {
+    presets: BTreeMap<String, Preset>,
+}
+
+impl SavedPresets {
+    fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text)
+                .with_context(|| format!("invalid presets {}.", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("failed to read {}.", path.display())),
+        }
+    }
+
+    /// Written to a temporary file first, so that a failed write doesn't lose the other presets.
+    fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}.", dir.display()))?;
+        }
+        let text = toml::to_string(self).context("failed to serialize presets.")?;
+        let tmp = path.with_extension("toml.tmp");
+        fs::write(&tmp, text).with_context(|| format!("failed to write {}.", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("failed to write {}.", path.display()))
+    }
+}
+
+/// `~/.config/kanten/config.toml`, command line flags take precedence over it.
+///
+/// ```toml
+/// since = "1h"
+/// group_name = "^/aws/lambda/api-"
+///
+/// [theme]
+/// highlight = "#484460"
+///
+/// [keys]
+/// preset = "vim"
+///
+/// [presets.errors]
+/// filter = "ERROR"
+/// mode = "filter"
+/// ```
+///
+/// Presets saved from the TUI are kept in `presets.toml` next to it and take precedence.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(flatten)]
+    pub defaults: Preset,
+    pub theme: Theme,
+    pub keys: Keymap,
+    pub presets: BTreeMap<String, Preset>,
+    #[serde(skip)]
+    saved_presets_path: PathBuf,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/kanten/config.toml`, falling back to `~/.config`.
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => dirs_next::home_dir()
+                .ok_or_else(|| anyhow!("failed to find home dir."))?
+                .join(".config"),
+        };
+        Ok(dir.join("kanten").join("config.toml"))
+    }
+
+    /// Load the config file, an empty config when it doesn't exist.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path, true),
+            None => (Self::default_path()?, false),
+        };
+        let mut config: Self = match fs::read_to_string(&path) {
+            Ok(text) => {
+                log::debug!("loaded config from {}", path.display());
+                toml::from_str(&text)
+                    .with_context(|| format!("invalid config {}.", path.display()))?
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound && !explicit => Self::default(),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}.", path.display())),
+        };
+        config.saved_presets_path = path.with_file_name(SAVED_PRESETS_FILE);
+        config
+            .presets
+            .extend(SavedPresets::load(&config.saved_presets_path)?.presets);
+        Ok(config)
+    }
+
+    /// Save a preset to `presets.toml`, replacing the one with the same name.
+    pub fn save_preset(&mut self, name: &str, preset: Preset) -> Result<PathBuf> {
+        let path = self.saved_presets_path.clone();
+        // Read it again, another kanten may have saved presets meanwhile.
+        let mut saved = SavedPresets::load(&path)?;
+        saved.presets.insert(name.to_owned(), preset.clone());
+        saved.write(&path)?;
+        self.presets.insert(name.to_owned(), preset);
+        Ok(path)
+    }
+}
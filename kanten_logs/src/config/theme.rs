@@ -0,0 +1,89 @@
+use serde::{de::Error, Deserialize, Deserializer};
+use tui::style::Color;
+
+/// Colours of the TUI, set in the `[theme]` table of the config file.
+///
+/// Colours are names like `"dark_gray"` or `"light_blue"`, or hex like `"#484460"`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Background of the selected row.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub highlight: Color,
+    /// Border of the focused pane.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border: Color,
+    /// Border of the other panes.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub inactive_border: Color,
+    /// Secondary text, like timestamps and hints.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub muted: Color,
+    /// Header of the selected table column, drawn reversed.
+    #[serde(deserialize_with = "deserialize_color")]
+    pub header: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            highlight: Color::Rgb(72, 68, 96),
+            border: Color::White,
+            inactive_border: Color::DarkGray,
+            muted: Color::DarkGray,
+            header: Color::White,
+        }
+    }
+}
+
+fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_color(&s).ok_or_else(|| D::Error::custom(format!("unknown color {}", s)))
+}
+
+fn parse_color(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        // Also keeps the slicing below on char boundaries.
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    let color = match s.to_lowercase().replace('-', "_").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "dark_gray" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_hex() {
+        assert_eq!(parse_color("Light-Blue"), Some(Color::LightBlue));
+        assert_eq!(parse_color("#484460"), Some(Color::Rgb(72, 68, 96)));
+        assert_eq!(parse_color("#aééb"), None);
+        assert_eq!(parse_color("#+f+f+f"), None);
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("mauve"), None);
+    }
+}
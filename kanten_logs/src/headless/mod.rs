@@ -0,0 +1,104 @@
+use std::{collections::HashMap, io::stdout};
+
+use anyhow::{bail, Context, Result};
+
+use crate::client::*;
+use crate::components::GroupList;
+use crate::models::{Duration, ExportFormat, SearchMode};
+use crate::option::Opt;
+
+/// Run a single search without the TUI and print its results to stdout.
+///
+/// Returns whether anything matched, so that scripts can tell "no results" apart from errors.
+pub async fn run<C: Backend>(client: C, opt: Opt, format: ExportFormat) -> Result<bool> {
+    // Same group selection as the TUI starts with.
+    let group_name = opt.group_name.clone().unwrap_or_default();
+    if group_name.is_empty() {
+        bail!("--group-name is required with --output.");
+    }
+    regex::Regex::new(&group_name).context("invalid --group-name.")?;
+    let group_names = client.get_group_names().await?;
+    let groups: Vec<String> = GroupList::with_items(group_names.items, group_name.clone(), true)
+        .selected
+        .into_iter()
+        .collect();
+    if groups.is_empty() {
+        bail!("no log groups match {}.", group_name);
+    }
+
+    let (start, end) = match Duration::from_opt(opt.since(), opt.end.as_deref()) {
+        Duration::Duration {
+            start: Some(start),
+            end: Some(end),
+        } => (start, end),
+        Duration::Live => bail!("live tail isn't supported with --output."),
+        _ => bail!("invalid duration {} - {:?}.", opt.since(), opt.end),
+    };
+
+    let items: Vec<SearchResultItem> = match opt.mode() {
+        SearchMode::Insights | SearchMode::Query => {
+            let query = if opt.mode() == SearchMode::Query {
+                opt.filter().to_owned()
+            } else {
+                default_query(opt.filter())
+            };
+            let input = StartQueryInput {
+                start,
+                end,
+                query,
+                groups,
+                stream_names: HashMap::new(),
+            };
+            wait_for_query(&client, input).await?
+        }
+        SearchMode::Filter => {
+            let input = FilterLogsInput {
+                start,
+                end,
+                groups,
+                stream_name_prefix: opt.stream_prefix.clone(),
+                stream_names: HashMap::new(),
+                filter_pattern: opt.filter().to_owned(),
+                cursor: FilterLogsCursor::default(),
+            };
+            let (items, truncated) = filter_all(&client, input, opt.max_events()).await?;
+            if truncated {
+                eprintln!(
+                    "kanten: stopped after {} events, raise --max-events to get more.",
+                    items.len()
+                );
+            }
+            items.into_iter().map(SearchResultItem::from).collect()
+        }
+    };
+
+    let rows: Vec<Vec<(String, String)>> = items.into_iter().map(|item| item.fields).collect();
+    format.write(&mut stdout().lock(), &rows)?;
+    Ok(!rows.is_empty())
+}
+
+/// Poll the query until it completes, stopping it when interrupted.
+async fn wait_for_query<C: Backend>(
+    client: &C,
+    input: StartQueryInput,
+) -> Result<Vec<SearchResultItem>> {
+    let query_id = client.start_query(input).await?;
+    loop {
+        let result = tokio::select! {
+            result = client.get_query_results(&query_id) => result?,
+            _ = tokio::signal::ctrl_c() => {
+                client.stop_query(&query_id).await?;
+                bail!("interrupted.");
+            }
+        };
+        match result {
+            SearchResult::Complete(items, statistics) => {
+                if statistics.slices > 1 {
+                    log::info!("results were stitched from {} queries", statistics.slices);
+                }
+                return Ok(items);
+            }
+            SearchResult::Running(..) => tokio::time::sleep(crate::QUERY_POLL_INTERVAL).await,
+        }
+    }
+}
@@ -0,0 +1,388 @@
+mod key;
+
+use std::{collections::BTreeMap, convert::TryFrom, fmt};
+
+use anyhow::{anyhow, Error, Result};
+use crossterm::event::KeyEvent;
+use serde::{
+    de::{value, IntoDeserializer},
+    Deserialize,
+};
+
+pub use key::{text, Key};
+
+/// What a key does, named so that it can be bound in the `[keys]` table of the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    FocusNext,
+    FocusPrev,
+    Help,
+    Notifications,
+    Export,
+    SavePreset,
+    OpenPresets,
+    SwitchMode,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Left,
+    Right,
+    Select,
+    Close,
+    OpenContext,
+    BackToEvent,
+    Copy,
+    CopyMessage,
+    PrevBucket,
+    NextBucket,
+    Zoom,
+    WidenColumn,
+    NarrowColumn,
+    HideColumn,
+    ShowColumns,
+    Sort,
+    Submit,
+    Cancel,
+    LineStart,
+    LineEnd,
+    CharForward,
+    CharBackward,
+    WordForward,
+    WordBackward,
+    DeleteChar,
+    DeleteCharBackward,
+    KillWord,
+    KillWordBackward,
+    KillToEnd,
+    KillToStart,
+    Yank,
+    HistoryPrev,
+    HistoryNext,
+    HistorySearch,
+}
+
+/// The name in the config file, like `page_up`.
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = format!("{:?}", self);
+        for (i, c) in name.chars().enumerate() {
+            if c.is_uppercase() && i > 0 {
+                write!(f, "_")?;
+            }
+            write!(f, "{}", c.to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+impl Action {
+    /// Shown in the help.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::FocusNext => "focus the next pane (toggles the option in prompts)",
+            Action::FocusPrev => "focus the previous pane",
+            Action::Help => "show the key bindings",
+            Action::Notifications => "show the notifications",
+            Action::Export => "export the results to a file",
+            Action::SavePreset => "save the search as a preset",
+            Action::OpenPresets => "load a preset",
+            Action::SwitchMode => "switch the search mode",
+            Action::Up => "previous item",
+            Action::Down => "next item",
+            Action::PageUp => "previous page",
+            Action::PageDown => "next page",
+            Action::Left => "previous column, or fold the field",
+            Action::Right => "next column, or unfold the field",
+            Action::Select => "select the item, open the event or fold the field",
+            Action::Close => "close the popup",
+            Action::OpenContext => "show the events around it in its stream",
+            Action::BackToEvent => "back to the event in its context",
+            Action::Copy => "copy the line, or the field in the detail",
+            Action::CopyMessage => "copy the message",
+            Action::PrevBucket => "select the previous histogram bucket",
+            Action::NextBucket => "select the next histogram bucket",
+            Action::Zoom => "narrow the duration to the selected bucket",
+            Action::WidenColumn => "widen the column",
+            Action::NarrowColumn => "narrow the column",
+            Action::HideColumn => "hide the column",
+            Action::ShowColumns => "show all columns",
+            Action::Sort => "sort by the column",
+            Action::Submit => "run the query, or confirm the prompt",
+            Action::Cancel => "cancel the history search or the prompt",
+            Action::LineStart => "start of the line",
+            Action::LineEnd => "end of the line",
+            Action::CharForward => "next character",
+            Action::CharBackward => "previous character",
+            Action::WordForward => "next word",
+            Action::WordBackward => "previous word",
+            Action::DeleteChar => "delete the character",
+            Action::DeleteCharBackward => "delete the previous character",
+            Action::KillWord => "kill the word after the cursor",
+            Action::KillWordBackward => "kill the word before the cursor",
+            Action::KillToEnd => "kill to the end of the line",
+            Action::KillToStart => "kill to the start of the line",
+            Action::Yank => "yank the killed text",
+            Action::HistoryPrev => "previous value in the history",
+            Action::HistoryNext => "next value in the history",
+            Action::HistorySearch => "search the history, again for older matches",
+        }
+    }
+}
+
+/// Where bindings apply. Keys are looked up in the contexts of the focused pane in turn,
+/// e.g. `global`, `logs` then `list` in the logs pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Context {
+    Global,
+    /// Line editing in the inputs and prompts.
+    Input,
+    /// Moving in lists, tables and popups.
+    List,
+    /// The logs pane and the detail and context of an event.
+    Logs,
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Context::Global => write!(f, "global"),
+            Context::Input => write!(f, "input"),
+            Context::List => write!(f, "list"),
+            Context::Logs => write!(f, "logs"),
+        }
+    }
+}
+
+/// Bindings to start from, the ones of the config file replace theirs.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeymapPreset {
+    Emacs,
+    /// hjkl and Ctrl-D/Ctrl-U in lists, and `q` instead of Esc to quit.
+    Vim,
+}
+
+impl Default for KeymapPreset {
+    fn default() -> Self {
+        KeymapPreset::Emacs
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keys {
+    fn parse(&self) -> Result<Vec<Key>> {
+        match self {
+            Keys::One(key) => Ok(vec![key.parse()?]),
+            Keys::Many(keys) => keys.iter().map(|key| key.parse()).collect(),
+        }
+    }
+}
+
+/// The `[keys]` table of the config file.
+///
+/// ```toml
+/// [keys]
+/// preset = "vim"
+///
+/// [keys.list]
+/// down = ["j", "down"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeysConfig {
+    preset: KeymapPreset,
+    // Keyed by name, as toml can't deserialize keys into an enum.
+    global: BTreeMap<String, Keys>,
+    input: BTreeMap<String, Keys>,
+    list: BTreeMap<String, Keys>,
+    logs: BTreeMap<String, Keys>,
+}
+
+/// Named actions of the keys in each context.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "KeysConfig")]
+pub struct Keymap {
+    bindings: BTreeMap<Context, Vec<(Action, Vec<Key>)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset(KeymapPreset::default())
+    }
+}
+
+impl TryFrom<KeysConfig> for Keymap {
+    type Error = Error;
+
+    fn try_from(config: KeysConfig) -> Result<Self> {
+        let mut keymap = Self::preset(config.preset);
+        let contexts = [
+            (Context::Global, &config.global),
+            (Context::Input, &config.input),
+            (Context::List, &config.list),
+            (Context::Logs, &config.logs),
+        ];
+        for (context, bindings) in contexts.iter() {
+            for (name, keys) in bindings.iter() {
+                // Serde only shows the outermost message, so the causes go into it.
+                let action = Action::deserialize(name.as_str().into_deserializer()).map_err(
+                    |e: value::Error| anyhow!("invalid action {}.{}: {}", context, name, e),
+                )?;
+                let keys = keys
+                    .parse()
+                    .map_err(|e| anyhow!("invalid keys for {}.{}: {}", context, action, e))?;
+                keymap.set(*context, action, keys);
+            }
+        }
+        Ok(keymap)
+    }
+}
+
+impl Keymap {
+    pub fn preset(preset: KeymapPreset) -> Self {
+        let mut keymap = Self {
+            bindings: BTreeMap::new(),
+        };
+        let global = [
+            (Action::Quit, &["esc", "ctrl-c"][..]),
+            (Action::FocusNext, &["tab"]),
+            (Action::FocusPrev, &["ctrl-tab", "shift-tab"]),
+            (Action::Help, &["f1"]),
+            (Action::Notifications, &["f2"]),
+            (Action::Export, &["ctrl-o"]),
+            (Action::SavePreset, &["ctrl-s"]),
+            (Action::OpenPresets, &["ctrl-l"]),
+            (Action::SwitchMode, &["ctrl-t"]),
+        ];
+        let input = [
+            (Action::Submit, &["enter"][..]),
+            (Action::Cancel, &["esc", "ctrl-g"]),
+            (Action::LineStart, &["ctrl-a", "home"]),
+            (Action::LineEnd, &["ctrl-e", "end"]),
+            (Action::CharForward, &["ctrl-f", "right"]),
+            (Action::CharBackward, &["ctrl-b", "left"]),
+            (Action::WordForward, &["alt-f", "ctrl-right"]),
+            (Action::WordBackward, &["alt-b", "ctrl-left"]),
+            (Action::DeleteChar, &["ctrl-d", "delete"]),
+            (Action::DeleteCharBackward, &["ctrl-h", "backspace"]),
+            (Action::KillWord, &["alt-d"]),
+            (Action::KillWordBackward, &["ctrl-w", "alt-backspace"]),
+            (Action::KillToEnd, &["ctrl-k"]),
+            (Action::KillToStart, &["ctrl-u"]),
+            (Action::Yank, &["ctrl-y"]),
+            (Action::HistoryPrev, &["up"]),
+            (Action::HistoryNext, &["down"]),
+            (Action::HistorySearch, &["ctrl-r"]),
+        ];
+        let list = [
+            (Action::Up, &["ctrl-p", "up"][..]),
+            (Action::Down, &["ctrl-n", "down"]),
+            (Action::PageUp, &["alt-v", "pageup"]),
+            (Action::PageDown, &["ctrl-v", "pagedown"]),
+            (Action::Left, &["ctrl-b", "left"]),
+            (Action::Right, &["ctrl-f", "right"]),
+            (Action::Select, &["enter", "space"]),
+            (Action::Close, &["esc", "q"]),
+            (Action::Help, &["?"]),
+        ];
+        let logs = [
+            (Action::OpenContext, &["c"][..]),
+            (Action::BackToEvent, &["."]),
+            (Action::Copy, &["y"]),
+            (Action::CopyMessage, &["m"]),
+            (Action::PrevBucket, &["["]),
+            (Action::NextBucket, &["]"]),
+            (Action::Zoom, &["z"]),
+            (Action::WidenColumn, &["+"]),
+            (Action::NarrowColumn, &["-"]),
+            (Action::HideColumn, &["x"]),
+            (Action::ShowColumns, &["a"]),
+            (Action::Sort, &["s"]),
+        ];
+        let vim: &[(Context, Action, &[&str])] = match preset {
+            KeymapPreset::Emacs => &[],
+            KeymapPreset::Vim => &[
+                (Context::Global, Action::Quit, &["q", "ctrl-c"]),
+                (Context::List, Action::Up, &["k", "up"]),
+                (Context::List, Action::Down, &["j", "down"]),
+                (Context::List, Action::PageUp, &["ctrl-u", "pageup"]),
+                (Context::List, Action::PageDown, &["ctrl-d", "pagedown"]),
+                (Context::List, Action::Left, &["h", "left"]),
+                (Context::List, Action::Right, &["l", "right"]),
+            ],
+        };
+        let defaults = [
+            (Context::Global, &global[..]),
+            (Context::Input, &input[..]),
+            (Context::List, &list[..]),
+            (Context::Logs, &logs[..]),
+        ];
+        let bindings = defaults
+            .iter()
+            .flat_map(|(context, bindings)| bindings.iter().map(move |(a, k)| (*context, *a, *k)))
+            .chain(vim.iter().copied());
+        for (context, action, keys) in bindings {
+            let keys = keys
+                .iter()
+                .map(|key| key.parse().expect("invalid default key"))
+                .collect();
+            keymap.set(context, action, keys);
+        }
+        keymap
+    }
+
+    /// Bind the keys to the action, replacing its keys and unbinding them from other actions.
+    pub fn set(&mut self, context: Context, action: Action, keys: Vec<Key>) {
+        let bindings = self.bindings.entry(context).or_default();
+        for (_, bound) in bindings.iter_mut() {
+            bound.retain(|key| !keys.contains(key));
+        }
+        match bindings.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, bound)) => *bound = keys,
+            None => bindings.push((action, keys)),
+        }
+    }
+
+    pub fn action(&self, context: Context, key: &KeyEvent) -> Option<Action> {
+        let key = Key::from(key);
+        self.bindings
+            .get(&context)?
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    /// The action of the key in the first of the contexts that binds it.
+    pub fn find(&self, contexts: &[Context], key: &KeyEvent) -> Option<Action> {
+        contexts
+            .iter()
+            .find_map(|context| self.action(*context, key))
+    }
+
+    /// The first key of the action, for hints like `esc: close`.
+    pub fn hint(&self, context: Context, action: Action) -> String {
+        self.bindings(context)
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, keys)| keys.first())
+            .map_or_else(|| "unbound".to_owned(), Key::to_string)
+    }
+
+    /// Actions of the context with their keys, in the order they were bound.
+    pub fn bindings(&self, context: Context) -> impl Iterator<Item = &(Action, Vec<Key>)> {
+        self.bindings
+            .get(&context)
+            .into_iter()
+            .flatten()
+            .filter(|(_, keys)| !keys.is_empty())
+    }
+}
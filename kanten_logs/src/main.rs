@@ -3,22 +3,27 @@ use async_trait::async_trait;
 #[allow(dead_code)]
 mod app;
 mod client;
+mod clipboard;
 mod components;
+mod config;
+mod headless;
+mod keymap;
 mod models;
 mod option;
 
 use std::sync::mpsc::Sender;
 // use cloudwatchlogs::{Config, Credentials, Region};
 // https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_StartQuery.html
-use crate::{
-    app::{view, App, Dispatcher, Message},
-    client::{GroupsClient, QueryClient},
+use crate::app::{view, App, Dispatcher, Message};
+use client::{
+    Backend, Client, FilterLogsInput, FilterOutput, LocalClient, SearchResult, SlicingClient,
 };
-use client::{Client, SearchResult};
+use models::Notification;
 
 use crossterm::{
     event::{
-        poll, read, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyEvent,
+        poll, read, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+        EnableMouseCapture, Event as CEvent, KeyEventKind,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -76,40 +81,87 @@ pub trait AsyncTask {
     async fn run(&mut self, message: Message) -> Option<Message>;
 }
 
-struct Service {
-    pub client: client::Client,
+/// How long to wait before asking again for the results of a running query.
+const QUERY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+struct Service<C: Backend> {
+    pub client: C,
     // pub query_id: Option<crate::client::QueryId>,
 }
 
 #[async_trait]
-impl AsyncTask for Service {
+impl<C: Backend> AsyncTask for Service<C> {
     async fn run(&mut self, message: Message) -> Option<Message> {
         match message {
             Message::GetQueryResultsRequest(query_id) => {
                 log::trace!("request query result");
-                if let Ok(SearchResult::Complete(items)) =
-                    self.client.get_default_query_results(&query_id).await
-                {
-                    log::trace!("items {}", items.len());
-                    Some(Message::GetQueryResultsComplete(items))
-                } else {
-                    // TODO: handle error
-                    std::thread::sleep(Duration::from_millis(100));
-                    self.run(Message::GetQueryResultsRequest(query_id)).await
+                match self.client.get_query_results(&query_id).await {
+                    Ok(SearchResult::Complete(items, statistics)) => {
+                        log::trace!("items {}", items.len());
+                        Some(Message::GetQueryResultsComplete(
+                            query_id, items, statistics,
+                        ))
+                    }
+                    Ok(SearchResult::Running(query_id, items, statistics)) => {
+                        log::trace!("partial items {}", items.len());
+                        Some(Message::GetQueryResultsRunning(query_id, items, statistics))
+                    }
+                    Err(e) => Some(Message::QueryFailed(query_id, format!("{:#}", e))),
                 }
             }
-            Message::StartQueryRequest(input) => {
+            Message::StartQueryRequest(id, input) => {
                 log::debug!("start query");
-                let query_id = self
-                    .client
-                    .start_default_query(input)
-                    .await
-                    .expect("Failed to start query");
-                Some(Message::StartQueryComplete(query_id))
+                match self.client.start_query(input).await {
+                    Ok(query_id) => Some(Message::StartQueryComplete(id, query_id)),
+                    Err(e) => Some(Message::StartQueryFailed(id, format!("{:#}", e))),
+                }
+            }
+            Message::StopQueryRequest(query_id) => match self.client.stop_query(&query_id).await {
+                Ok(_) => None,
+                Err(e) => Some(Message::Notify(Notification::warning(format!("{:#}", e)))),
+            },
+            Message::GetStreamsRequest(group, since) => {
+                match self.client.get_streams(&group, since).await {
+                    Ok(output) => Some(Message::GetStreamsComplete(group, output.items)),
+                    Err(e) => Some(Message::GetStreamsFailed(group, format!("{:#}", e))),
+                }
+            }
+            Message::GetLogEventsRequest(input) => {
+                match self.client.get_log_events(input.clone()).await {
+                    Ok(output) => Some(Message::GetLogEventsComplete(input, output)),
+                    Err(e) => Some(Message::GetLogEventsFailed(input, format!("{:#}", e))),
+                }
             }
-            Message::StopQueryRequest(query_id) => {
-                let _ = self.client.stop_query(&query_id).await;
-                None
+            Message::GetQueryDefinitionsRequest => {
+                match self.client.get_query_definitions().await {
+                    Ok(definitions) => Some(Message::GetQueryDefinitionsComplete(definitions)),
+                    Err(e) => Some(Message::GetQueryDefinitionsFailed(format!("{:#}", e))),
+                }
+            }
+            Message::PutQueryDefinitionRequest(definition) => {
+                let name = definition.name.clone();
+                match self.client.put_query_definition(definition).await {
+                    Ok(_) => Some(Message::Notify(Notification::info(format!(
+                        "saved query definition {} to CloudWatch Logs.",
+                        name
+                    )))),
+                    Err(e) => Some(Message::Notify(Notification::error(format!("{:#}", e)))),
+                }
+            }
+            Message::FilterLogsRequest(id, input) => {
+                log::debug!("filter log events");
+                match self.client.filter_logs(input.clone()).await {
+                    Ok(FilterOutput {
+                        items,
+                        next: Some(cursor),
+                    }) => Some(Message::FilterLogsRunning(
+                        id,
+                        items,
+                        FilterLogsInput { cursor, ..input },
+                    )),
+                    Ok(output) => Some(Message::FilterLogsComplete(id, output.items)),
+                    Err(e) => Some(Message::FilterLogsFailed(id, format!("{:#}", e))),
+                }
             }
             _ => Some(message),
         }
@@ -118,12 +170,64 @@ impl AsyncTask for Service {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    setup_logging()?;
-
     let opt = option::Opt::from_args();
+    let headless = opt.output.is_some();
+    check(setup_logging(), headless)?;
+
+    let config = check(config::Config::load(opt.config.clone()), headless)?;
+    let opt = check(opt.merge(&config), headless)?;
+
+    if let Some(ref dir) = opt.local {
+        let client = check(LocalClient::from_dir(dir), headless)?;
+        return start(SlicingClient::new(client), opt, config).await;
+    }
 
     let shared_config = aws_config::load_from_env().await;
-    let client = Client::new(cloudwatchlogs::Client::new(&shared_config));
+    let client = check(
+        Client::from_shared_config(&shared_config, opt.endpoint_url.as_deref(), opt.localstack),
+        headless,
+    )?;
+    start(SlicingClient::new(client), opt, config).await
+}
+
+/// With `--output`, errors before the search also exit with 2.
+fn check<T>(result: Result<T>, headless: bool) -> Result<T, Box<dyn Error>> {
+    match result {
+        Ok(value) => Ok(value),
+        Err(e) if headless => {
+            eprintln!("kanten: {:#}", e);
+            std::process::exit(2);
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Print the results with `--output`, otherwise start the TUI.
+async fn start<C: Backend>(
+    client: C,
+    opt: option::Opt,
+    config: config::Config,
+) -> Result<(), Box<dyn Error>> {
+    let format = match opt.output {
+        Some(format) => format,
+        None => return run(client, opt, config).await,
+    };
+    // Like grep: 0 when something matched, 1 when nothing did and 2 on errors.
+    match headless::run(client, opt, format).await {
+        Ok(true) => Ok(()),
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("kanten: {:#}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+async fn run<C: Backend>(
+    client: C,
+    opt: option::Opt,
+    config: config::Config,
+) -> Result<(), Box<dyn Error>> {
     let group_names = client.get_group_names().await?;
 
     let (tx0, rx0) = mpsc::channel::<Message>();
@@ -133,7 +237,12 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let mut stdout = stdout();
 
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
 
     let backend = CrosstermBackend::new(stdout);
 
@@ -150,8 +259,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .checked_sub(last_tick.elapsed())
                 .unwrap_or_else(|| Duration::from_secs(0));
             if poll(timeout).unwrap() {
-                if let CEvent::Key(key) = read().unwrap() {
-                    tx2.send(Message::KeyInput(key)).unwrap();
+                match read().unwrap() {
+                    // Windows also reports key releases.
+                    CEvent::Key(key) if key.kind != KeyEventKind::Release => {
+                        tx2.send(Message::KeyInput(key)).unwrap()
+                    }
+                    CEvent::Paste(text) => tx2.send(Message::Paste(text)).unwrap(),
+                    _ => {}
                 }
             }
             if last_tick.elapsed() >= tick_rate {
@@ -178,28 +292,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     });
 
-    let mut app = App::new(messenger, group_names.items, opt);
+    let mut app = App::new(messenger, group_names.items, opt, config);
     terminal.clear()?;
 
     loop {
         terminal.draw(|f| view::draw(f, &mut app))?;
         let message = rx0.recv()?;
         match message {
-            Message::KeyInput(key) => match key {
-                KeyEvent {
-                    code: KeyCode::Esc, ..
-                } => {
-                    disable_raw_mode()?;
-                    execute!(
-                        terminal.backend_mut(),
-                        LeaveAlternateScreen,
-                        DisableMouseCapture
-                    )?;
-                    terminal.show_cursor()?;
-                    break;
-                }
-                _ => app.on_key(key)?,
-            },
+            Message::KeyInput(key) => app.on_key(key)?,
             // TODO: remove await
             _ => app.update(message).await,
         }
@@ -207,5 +307,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
             break;
         }
     }
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
+    terminal.show_cursor()?;
     Ok(())
 }
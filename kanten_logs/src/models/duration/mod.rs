@@ -3,7 +3,8 @@ use humantime::{parse_duration, parse_rfc3339};
 
 #[derive(Debug)]
 pub enum Duration {
-    // Live,
+    /// Keep following new events, like `aws logs tail --follow`.
+    Live,
     Duration {
         start: Option<i64>,
         end: Option<i64>,
@@ -20,7 +21,7 @@ fn parse(s: &str) -> Option<i64> {
         Some((now - chrono::Duration::seconds(d.as_secs() as i64)).timestamp())
     } else {
         if let Ok(d) = parse_rfc3339(s) {
-            if let Ok(d) = d.elapsed() {
+            if let Ok(d) = d.duration_since(std::time::UNIX_EPOCH) {
                 return Some(d.as_secs() as i64);
             }
         }
@@ -30,6 +31,9 @@ fn parse(s: &str) -> Option<i64> {
 
 impl Duration {
     pub fn from_opt(s: &str, e: Option<&str>) -> Self {
+        if s == "live" {
+            return Self::Live;
+        }
         let end = if let Some(e) = e {
             parse(e)
         } else {
@@ -44,16 +48,40 @@ impl Duration {
     pub fn is_valid(&self) -> bool {
         match self {
             Duration::Duration { start, end } => start.is_some() && end.is_some(),
-            // _ => todo!(),
+            Duration::Live => true,
         }
     }
 }
 
 impl From<&str> for Duration {
     fn from(s: &str) -> Self {
-        let s: Vec<Option<i64>> = s.split('-').map(|s| parse(s.trim())).collect();
-        let start: Option<i64> = s.get(0).and_then(|i| *i);
-        let end: Option<i64> = s.get(1).and_then(|i| *i);
+        if s.trim() == "live" {
+            return Duration::Live;
+        }
+        let (start, end) = split_range(s);
+        let start: Option<i64> = parse(start);
+        // Without an end, the duration runs until now.
+        let end: Option<i64> = match end {
+            Some(s) => parse(s),
+            None => parse("now"),
+        };
         Duration::Duration { start, end }
     }
 }
+
+/// Split a duration input like "3h - 1h" into its trimmed start and end.
+pub fn split_range(s: &str) -> (&str, Option<&str>) {
+    // RFC3339 timestamps contain '-', so prefer a spaced separator when there is one.
+    let mut parts: Vec<&str> = if s.contains(" - ") {
+        s.split(" - ").collect()
+    } else if parse(s.trim()).is_some() {
+        vec![s]
+    } else {
+        s.split('-').collect()
+    };
+    parts.truncate(2);
+    (
+        parts.first().map(|s| s.trim()).unwrap_or_default(),
+        parts.get(1).map(|s| s.trim()),
+    )
+}
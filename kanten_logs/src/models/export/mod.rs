@@ -0,0 +1,117 @@
+use std::{fmt, io::Write, path::Path, str::FromStr};
+
+use anyhow::{bail, Error, Result};
+
+/// File format results are exported in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExportFormat {
+    /// One JSON object of all fields per line.
+    JsonLines,
+    /// A column per field, in the order they first appear.
+    Csv,
+    /// `@timestamp` and `@message`, like the Logs pane shows them.
+    Text,
+}
+
+impl ExportFormat {
+    /// Guess the format from the extension, falling back to plain text.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("jsonl") | Some("ndjson") | Some("json") => ExportFormat::JsonLines,
+            Some("csv") => ExportFormat::Csv,
+            _ => ExportFormat::Text,
+        }
+    }
+
+    /// Write the rows, each a list of `(field, value)`.
+    pub fn write(self, w: &mut impl Write, rows: &[Vec<(String, String)>]) -> Result<()> {
+        match self {
+            ExportFormat::JsonLines => {
+                for row in rows {
+                    let object: serde_json::Map<String, serde_json::Value> = row
+                        .iter()
+                        .map(|(name, value)| (name.clone(), value.clone().into()))
+                        .collect();
+                    serde_json::to_writer(&mut *w, &object)?;
+                    writeln!(w)?;
+                }
+            }
+            ExportFormat::Csv => {
+                let mut columns: Vec<&str> = vec![];
+                for (name, _) in rows.iter().flatten() {
+                    if !columns.contains(&name.as_str()) {
+                        columns.push(name);
+                    }
+                }
+                write_csv_record(w, columns.iter().copied())?;
+                for row in rows {
+                    write_csv_record(
+                        w,
+                        columns.iter().map(|column| {
+                            row.iter()
+                                .find(|(name, _)| name == column)
+                                .map(|(_, value)| value.as_str())
+                                .unwrap_or_default()
+                        }),
+                    )?;
+                }
+            }
+            ExportFormat::Text => {
+                for row in rows {
+                    let get = |field: &str| row.iter().find(|(name, _)| name == field);
+                    match (get("@timestamp"), get("@message")) {
+                        (Some((_, timestamp)), Some((_, message))) => {
+                            writeln!(w, "{}    {}", timestamp, message.trim_end())?
+                        }
+                        // Rows without a message, e.g. from `stats`.
+                        _ => {
+                            let values: Vec<&str> = row.iter().map(|(_, v)| v.as_str()).collect();
+                            writeln!(w, "{}", values.join("\t"))?
+                        }
+                    }
+                }
+            }
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
+
+fn write_csv_record<'a>(
+    w: &mut impl Write,
+    values: impl Iterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    let values: Vec<String> = values
+        .map(|v| {
+            if v.contains(&[',', '"', '\n', '\r'][..]) {
+                format!("\"{}\"", v.replace('"', "\"\""))
+            } else {
+                v.to_owned()
+            }
+        })
+        .collect();
+    write!(w, "{}\r\n", values.join(","))
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::JsonLines => write!(f, "json"),
+            ExportFormat::Csv => write!(f, "csv"),
+            ExportFormat::Text => write!(f, "text"),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" | "jsonl" => Ok(ExportFormat::JsonLines),
+            "csv" => Ok(ExportFormat::Csv),
+            "text" => Ok(ExportFormat::Text),
+            _ => bail!("unknown format {}, expected json, csv or text.", s),
+        }
+    }
+}
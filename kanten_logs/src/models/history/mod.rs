@@ -0,0 +1,148 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, ErrorKind, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+
+/// How many values are kept per input.
+const MAX_ENTRIES: usize = 500;
+
+/// Past values of an input, oldest first, appended to a JSON Lines file so that
+/// every session (and every running kanten) shares them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct History {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+    /// The entry shown while browsing with Up/Down.
+    index: Option<usize>,
+    /// What was typed before browsing, shown again after the newest entry.
+    draft: String,
+}
+
+impl History {
+    /// Read the history file, which is compacted when it grew past the limit.
+    /// Without a path the history only lasts for the session.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let mut history = Self {
+            path,
+            ..Default::default()
+        };
+        let path = match history.path {
+            Some(ref path) => path.clone(),
+            None => return Ok(history),
+        };
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(history),
+            Err(e) => return Err(e).with_context(|| format!("failed to open {}.", path.display())),
+        };
+        let mut lines = 0;
+        for line in BufReader::new(file).lines() {
+            let line = line.with_context(|| format!("failed to read {}.", path.display()))?;
+            lines += 1;
+            // Skip lines broken by a concurrent write.
+            if let Ok(value) = serde_json::from_str::<String>(&line) {
+                history.insert(value);
+            }
+        }
+        if lines > MAX_ENTRIES * 2 {
+            history.rewrite()?;
+        }
+        Ok(history)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Remember a value as the newest entry and stop browsing.
+    pub fn push(&mut self, value: &str) -> Result<()> {
+        self.reset();
+        if value.trim().is_empty() || self.entries.last().map(String::as_str) == Some(value) {
+            return Ok(());
+        }
+        self.insert(value.to_owned());
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}.", dir.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open {}.", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(value)?)
+            .with_context(|| format!("failed to write {}.", path.display()))
+    }
+
+    /// The entry before the shown one, `current` is kept to come back to.
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let index = match self.index {
+            Some(0) => return None,
+            Some(i) => i - 1,
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_owned();
+                self.entries.len() - 1
+            }
+        };
+        self.index = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// The entry after the shown one, then what was typed before browsing.
+    pub fn next(&mut self) -> Option<&str> {
+        let index = self.index?;
+        if index + 1 < self.entries.len() {
+            self.index = Some(index + 1);
+            self.entries.get(index + 1).map(String::as_str)
+        } else {
+            self.index = None;
+            Some(self.draft.as_str())
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.index = None;
+        self.draft.clear();
+    }
+
+    /// Index of the newest entry before `before` containing `query`.
+    pub fn find(&self, query: &str, before: usize) -> Option<usize> {
+        let before = before.min(self.entries.len());
+        self.entries[..before]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+    }
+
+    fn insert(&mut self, value: String) {
+        self.entries.retain(|entry| *entry != value);
+        self.entries.push(value);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+    }
+
+    fn rewrite(&self) -> Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        let tmp = path.with_extension("jsonl.tmp");
+        let mut w = BufWriter::new(
+            File::create(&tmp).with_context(|| format!("failed to create {}.", tmp.display()))?,
+        );
+        for entry in self.entries.iter() {
+            writeln!(w, "{}", serde_json::to_string(entry)?)?;
+        }
+        w.flush()?;
+        drop(w);
+        fs::rename(&tmp, path).with_context(|| format!("failed to write {}.", path.display()))
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::client::{FilterLogsCursor, FilterLogsInput, FilterOutputItem};
+
+/// How often new events are polled for.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How far back the first poll reaches.
+const BACKFILL_SECS: i64 = 60;
+
+/// Polls FilterLogEvents for events newer than the last one seen.
+///
+/// The API works in whole seconds here, so every poll re-reads the current
+/// second and already delivered events are dropped by their event id.
+#[derive(Debug)]
+pub struct LiveTail {
+    cursor: i64,
+    seen: HashMap<String, i64>,
+    last_polled_at: Option<Instant>,
+    in_flight: bool,
+}
+
+impl LiveTail {
+    pub fn new() -> Self {
+        Self {
+            cursor: chrono::Local::now().timestamp() - BACKFILL_SECS,
+            seen: HashMap::new(),
+            last_polled_at: None,
+            in_flight: false,
+        }
+    }
+
+    pub fn should_poll(&self) -> bool {
+        !self.in_flight
+            && self
+                .last_polled_at
+                .map(|t| t.elapsed() >= POLL_INTERVAL)
+                .unwrap_or(true)
+    }
+
+    pub fn poll(
+        &mut self,
+        groups: Vec<String>,
+        stream_name_prefix: Option<String>,
+        stream_names: HashMap<String, Vec<String>>,
+        filter_pattern: String,
+    ) -> FilterLogsInput {
+        self.in_flight = true;
+        self.last_polled_at = Some(Instant::now());
+        FilterLogsInput {
+            start: self.cursor,
+            end: chrono::Local::now().timestamp() + 1,
+            groups,
+            stream_name_prefix,
+            stream_names,
+            filter_pattern,
+            cursor: FilterLogsCursor::default(),
+        }
+    }
+
+    /// The poll fetched its last page or failed, poll again at the next interval.
+    pub fn finish(&mut self) {
+        self.in_flight = false;
+    }
+
+    /// Returns the events of a page that weren't delivered yet, oldest first.
+    pub fn accept(&mut self, items: Vec<FilterOutputItem>) -> Vec<FilterOutputItem> {
+        let mut items: Vec<FilterOutputItem> = items
+            .into_iter()
+            .filter(|item| !self.seen.contains_key(&item.event_id))
+            .collect();
+        items.sort_by_key(|item| item.timestamp);
+
+        if let Some(last) = items.last() {
+            self.cursor = self.cursor.max(last.timestamp / 1000);
+        }
+        for item in items.iter() {
+            self.seen.insert(item.event_id.clone(), item.timestamp);
+        }
+        let cursor_millis = self.cursor * 1000;
+        self.seen.retain(|_, timestamp| *timestamp >= cursor_millis);
+        items
+    }
+}
@@ -1,3 +1,15 @@
 pub mod duration;
+pub mod export;
+pub mod history;
+pub mod live_tail;
+pub mod notification;
+pub mod query_progress;
+pub mod search_mode;
 
 pub use duration::*;
+pub use export::*;
+pub use history::*;
+pub use live_tail::*;
+pub use notification::*;
+pub use query_progress::*;
+pub use search_mode::*;
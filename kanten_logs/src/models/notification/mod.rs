@@ -0,0 +1,126 @@
+use std::time::{Duration, Instant};
+
+use tui::widgets::ListState;
+
+/// How many notifications the history keeps.
+const HISTORY_SIZE: usize = 100;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Level {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Level {
+    /// How long a notification stays in the status bar.
+    fn timeout(self) -> Duration {
+        match self {
+            Level::Error => Duration::from_secs(10),
+            Level::Warning => Duration::from_secs(6),
+            Level::Info => Duration::from_secs(3),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    pub time: chrono::DateTime<chrono::Local>,
+    shown_at: Instant,
+}
+
+impl Notification {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            time: chrono::Local::now(),
+            shown_at: Instant::now(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Level::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Level::Warning, message)
+    }
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(Level::Info, message)
+    }
+
+    fn is_expired(&self) -> bool {
+        self.shown_at.elapsed() >= self.level.timeout()
+    }
+}
+
+/// Notifications shown in the status bar, and the history of past ones.
+#[derive(Debug, Clone)]
+pub struct Notifications {
+    /// Oldest first.
+    pub items: Vec<Notification>,
+    pub state: ListState,
+    dismissed: bool,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self {
+            items: vec![],
+            state: ListState::default(),
+            dismissed: false,
+        }
+    }
+
+    pub fn push(&mut self, notification: Notification) {
+        log::debug!("{:?}: {}", notification.level, notification.message);
+        self.dismissed = false;
+        // The same message repeated (e.g. while typing an invalid filter) only refreshes the last one.
+        if let Some(last) = self.items.last_mut() {
+            if last.level == notification.level && last.message == notification.message {
+                *last = notification;
+                return;
+            }
+        }
+        self.items.push(notification);
+        if self.items.len() > HISTORY_SIZE {
+            self.items.remove(0);
+        }
+    }
+
+    /// The latest notification, until it times out or is dismissed.
+    pub fn current(&self) -> Option<&Notification> {
+        if self.dismissed {
+            return None;
+        }
+        self.items.last().filter(|n| !n.is_expired())
+    }
+
+    pub fn dismiss(&mut self) {
+        self.dismissed = true;
+    }
+
+    /// Select the newest notification, as the history overlay lists them newest first.
+    pub fn select_latest(&mut self) {
+        self.state
+            .select(if self.items.is_empty() { None } else { Some(0) });
+    }
+
+    pub fn next(&mut self) {
+        if let Some(i) = self.state.selected() {
+            if i + 1 < self.items.len() {
+                self.state.select(Some(i + 1));
+            }
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+use crate::client::QueryStatistics;
+
+/// Statistics of the running (or last) Logs Insights query and how long it took.
+#[derive(Debug, Clone)]
+pub struct QueryProgress {
+    pub statistics: QueryStatistics,
+    started_at: Instant,
+    finished_in: Option<Duration>,
+}
+
+impl QueryProgress {
+    pub fn new() -> Self {
+        Self {
+            statistics: QueryStatistics::default(),
+            started_at: Instant::now(),
+            finished_in: None,
+        }
+    }
+
+    pub fn update(&mut self, statistics: QueryStatistics) {
+        self.statistics = statistics;
+    }
+
+    pub fn finish(&mut self, statistics: QueryStatistics) {
+        self.statistics = statistics;
+        self.finished_in = Some(self.started_at.elapsed());
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished_in.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.finished_in
+            .unwrap_or_else(|| self.started_at.elapsed())
+    }
+
+    /// Whether the query was split to get past the row limit.
+    pub fn is_stitched(&self) -> bool {
+        self.statistics.slices > 1
+    }
+
+    /// e.g. `matched 1,024 of 52,310 records, scanned 12.3 MB in 4.2s`
+    pub fn summary(&self) -> String {
+        let summary = format!(
+            "matched {} of {} records, scanned {} in {:.1}s",
+            format_count(self.statistics.records_matched),
+            format_count(self.statistics.records_scanned),
+            format_bytes(self.statistics.bytes_scanned),
+            self.elapsed().as_secs_f64()
+        );
+        if self.is_stitched() {
+            format!(
+                "{}, stitched from {} slices",
+                summary, self.statistics.slices
+            )
+        } else {
+            summary
+        }
+    }
+}
+
+fn format_count(n: f64) -> String {
+    let digits = format!("{}", n.max(0.0).round() as u64);
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes.max(0.0);
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
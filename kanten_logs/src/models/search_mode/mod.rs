@@ -0,0 +1,51 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Error};
+
+/// The API used to search the selected log groups.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SearchMode {
+    /// Logs Insights (StartQuery), capped at 10,000 rows and billed per scanned byte.
+    /// The input is a regular expression matched against `@message`.
+    Insights,
+    /// Logs Insights with a full query (`parse`, `stats`, `filter`, `display`, ...) sent verbatim.
+    Query,
+    /// FilterLogEvents, paginated through every matching event with filter pattern syntax.
+    Filter,
+}
+
+impl SearchMode {
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::Insights => SearchMode::Query,
+            SearchMode::Query => SearchMode::Filter,
+            SearchMode::Filter => SearchMode::Insights,
+        }
+    }
+}
+
+impl fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchMode::Insights => write!(f, "insights"),
+            SearchMode::Query => write!(f, "query"),
+            SearchMode::Filter => write!(f, "filter"),
+        }
+    }
+}
+
+impl FromStr for SearchMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "insights" => Ok(SearchMode::Insights),
+            "query" => Ok(SearchMode::Query),
+            "filter" => Ok(SearchMode::Filter),
+            _ => bail!(
+                "unknown search mode {}, expected insights, query or filter.",
+                s
+            ),
+        }
+    }
+}
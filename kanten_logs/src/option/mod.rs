@@ -1,11 +1,21 @@
+use std::path::PathBuf;
+
 use structopt::StructOpt;
 
+use anyhow::{anyhow, Result};
+
+use crate::client::DEFAULT_LIMIT;
+use crate::config::Config;
+use crate::models::{ExportFormat, SearchMode};
+
+const DEFAULT_SINCE: &str = "15m";
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "kanten")]
 pub struct Opt {
     /// Return logs newer than a relative duration like 52, 2m, or 3h. (default: "15m")
-    #[structopt(short, long, default_value = "15m")]
-    pub since: String,
+    #[structopt(short, long)]
+    pub since: Option<String>,
 
     /// Return logs older than a relative duration like 0, 2m, or 3h.
     #[structopt(short, long)]
@@ -15,6 +25,93 @@ pub struct Opt {
     #[structopt(short, long)]
     pub group_name: Option<String>,
 
-    #[structopt(short, long, default_value = "")]
-    pub filter: String,
+    #[structopt(short, long)]
+    pub filter: Option<String>,
+
+    /// Search with a Logs Insights regex ("insights"), a raw Logs Insights query ("query")
+    /// or FilterLogEvents ("filter"). Ctrl-T switches modes. (default: "insights")
+    #[structopt(short, long)]
+    pub mode: Option<SearchMode>,
+
+    /// Only search log streams starting with this prefix (filter mode).
+    #[structopt(long)]
+    pub stream_prefix: Option<String>,
+
+    /// Stop filter mode searches after this many events. (default: 10000)
+    #[structopt(long)]
+    pub max_events: Option<usize>,
+
+    /// Read log groups and events from a directory of JSON Lines fixtures instead of CloudWatch Logs.
+    #[structopt(long, parse(from_os_str))]
+    pub local: Option<PathBuf>,
+
+    /// Send CloudWatch Logs requests to this endpoint, e.g. a LocalStack or mock server.
+    #[structopt(long, env = "KANTEN_ENDPOINT_URL")]
+    pub endpoint_url: Option<String>,
+
+    /// Use LocalStack: defaults the endpoint to http://localhost:4566 and uses dummy credentials.
+    #[structopt(long)]
+    pub localstack: bool,
+
+    /// Print the results to stdout as "json" (JSON Lines), "csv" or "text" instead of starting the TUI.
+    /// Exits with 1 when nothing matched and 2 on errors.
+    #[structopt(short, long)]
+    pub output: Option<ExportFormat>,
+
+    /// Use the settings of a preset in the config file, flags still take precedence.
+    #[structopt(short, long)]
+    pub preset: Option<String>,
+
+    /// Config file to use instead of ~/.config/kanten/config.toml.
+    #[structopt(long, env = "KANTEN_CONFIG", parse(from_os_str))]
+    pub config: Option<PathBuf>,
+}
+
+impl Opt {
+    /// Fill the settings not given as flags from the preset, then the config file defaults.
+    pub fn merge(mut self, config: &Config) -> Result<Self> {
+        let mut sources = vec![];
+        if let Some(ref name) = self.preset {
+            sources.push(
+                config
+                    .presets
+                    .get(name)
+                    .ok_or_else(|| anyhow!("unknown preset {}.", name))?,
+            );
+        }
+        sources.push(&config.defaults);
+        for preset in sources {
+            // Without `--since`, `--end` of the config file would be mixed with another start.
+            if self.since.is_none() && preset.since.is_some() {
+                self.since = preset.since.clone();
+                self.end = self.end.take().or_else(|| preset.end.clone());
+            }
+            self.group_name = self.group_name.take().or_else(|| preset.group_pattern());
+            self.filter = self.filter.take().or_else(|| preset.filter.clone());
+            self.stream_prefix = self
+                .stream_prefix
+                .take()
+                .or_else(|| preset.stream_prefix.clone());
+            if let (None, Some(mode)) = (self.mode, preset.mode.as_deref()) {
+                self.mode = Some(mode.parse()?);
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn since(&self) -> &str {
+        self.since.as_deref().unwrap_or(DEFAULT_SINCE)
+    }
+
+    pub fn filter(&self) -> &str {
+        self.filter.as_deref().unwrap_or_default()
+    }
+
+    pub fn mode(&self) -> SearchMode {
+        self.mode.unwrap_or(SearchMode::Insights)
+    }
+
+    pub fn max_events(&self) -> usize {
+        self.max_events.unwrap_or(DEFAULT_LIMIT as usize)
+    }
 }